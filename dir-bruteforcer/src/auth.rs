@@ -0,0 +1,267 @@
+use crate::calibration::random_word;
+use clap::ValueEnum;
+use reqwest::header::{HeaderMap, WWW_AUTHENTICATE};
+use reqwest::{Method, RequestBuilder, Url};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum AuthType {
+    /// Send the username and password with every request
+    Basic,
+    /// Answer the server's Digest challenge, which is picked up from the first 401
+    Digest,
+    /// Send the value given to --auth as a bearer token
+    Bearer,
+}
+
+// A Digest challenge from a WWW-Authenticate header
+// Reference: https://datatracker.ietf.org/doc/html/rfc7616
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: String,
+    qop: Option<String>,
+}
+
+impl DigestChallenge {
+    // Only MD5 is supported, anything else is left for the server to keep rejecting
+    fn parse(headers: &HeaderMap) -> Option<(Self, bool)> {
+        let header = headers
+            .get_all(WWW_AUTHENTICATE)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .find(|value| value.len() > 7 && value[..7].eq_ignore_ascii_case("digest "))?;
+
+        let params = parse_params(&header[7..]);
+        let algorithm = params.get("algorithm").cloned().unwrap_or_else(|| "MD5".to_string());
+        if !algorithm.eq_ignore_ascii_case("md5") && !algorithm.eq_ignore_ascii_case("md5-sess") {
+            return None;
+        }
+
+        // Plain "auth" is all we can do, integrity protection would need the body hashed too
+        let qop = params.get("qop").and_then(|qop| {
+            qop.split(',')
+                .map(str::trim)
+                .find(|qop| qop.eq_ignore_ascii_case("auth"))
+                .map(String::from)
+        });
+
+        let stale = params.get("stale").is_some_and(|stale| stale.eq_ignore_ascii_case("true"));
+
+        let challenge = DigestChallenge {
+            realm: params.get("realm").cloned().unwrap_or_default(),
+            nonce: params.get("nonce").cloned()?,
+            opaque: params.get("opaque").cloned(),
+            algorithm,
+            qop,
+        };
+        Some((challenge, stale))
+    }
+}
+
+// Credentials for --auth, plus the Digest challenges seen so far. Challenges are kept per host so
+// several targets can be scanned with the same credentials.
+#[derive(Debug)]
+pub struct Auth {
+    auth_type: AuthType,
+    username: String,
+    password: String,
+    challenges: Mutex<HashMap<String, DigestChallenge>>,
+    nonce_count: AtomicU32,
+}
+
+impl Auth {
+    // "user:pass" for Basic and Digest, the token itself for Bearer
+    pub fn new(auth_type: AuthType, credentials: &str) -> Result<Self, String> {
+        let (username, password) = match auth_type {
+            AuthType::Bearer => (String::new(), credentials.to_string()),
+            _ => credentials
+                .split_once(':')
+                .map(|(username, password)| (username.to_string(), password.to_string()))
+                .ok_or_else(|| format!("{:?} auth needs credentials as 'user:pass'", auth_type))?,
+        };
+
+        Ok(Auth {
+            auth_type,
+            username,
+            password,
+            challenges: Mutex::new(HashMap::new()),
+            nonce_count: AtomicU32::new(0),
+        })
+    }
+
+    // Add the Authorization header. For Digest this only happens once a challenge has been seen
+    // from the host, until then the request goes out without it and gets a 401.
+    pub fn apply(&self, builder: RequestBuilder, method: &Method, url: &str) -> RequestBuilder {
+        match self.auth_type {
+            AuthType::Basic => builder.basic_auth(&self.username, Some(&self.password)),
+            AuthType::Bearer => builder.bearer_auth(&self.password),
+            AuthType::Digest => match self.digest_header(method, url) {
+                Some(header) => builder.header("Authorization", header),
+                None => builder,
+            },
+        }
+    }
+
+    // Whether a request to this URL will go out with an answer to a Digest challenge
+    pub fn has_challenge(&self, url: &str) -> bool {
+        host_of(url).is_some_and(|host| self.challenges.lock().unwrap().contains_key(&host))
+    }
+
+    // Look at a 401 and remember any new Digest challenge in it. Returns true if the request is
+    // worth sending again: it went out before we had a challenge (other requests can pick one up
+    // first), or the server has moved on to a new nonce.
+    pub fn handle_challenge(&self, url: &str, headers: &HeaderMap, answered: bool) -> bool {
+        if self.auth_type != AuthType::Digest {
+            return false;
+        }
+
+        let (Some(host), Some((challenge, stale))) = (host_of(url), DigestChallenge::parse(headers)) else {
+            return false;
+        };
+
+        let mut challenges = self.challenges.lock().unwrap();
+        let is_new = challenges.get(&host) != Some(&challenge);
+        challenges.insert(host, challenge);
+
+        !answered || is_new || stale
+    }
+
+    fn digest_header(&self, method: &Method, url: &str) -> Option<String> {
+        let parsed = Url::parse(url).ok()?;
+        let challenge = self.challenges.lock().unwrap().get(&host_of(url)?).cloned()?;
+
+        let uri = match parsed.query() {
+            Some(query) => format!("{}?{}", parsed.path(), query),
+            None => parsed.path().to_string(),
+        };
+
+        let cnonce = random_word(16);
+        let nc = format!("{:08x}", self.nonce_count.fetch_add(1, Ordering::Relaxed) + 1);
+        let response = self.digest_response(&challenge, method, &uri, &nc, &cnonce);
+
+        let mut header = format!(
+            "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", algorithm={}, response=\"{}\"",
+            self.username, challenge.realm, challenge.nonce, uri, challenge.algorithm, response
+        );
+        if let Some(qop) = &challenge.qop {
+            header.push_str(&format!(", qop={}, nc={}, cnonce=\"{}\"", qop, nc, cnonce));
+        }
+        if let Some(opaque) = &challenge.opaque {
+            header.push_str(&format!(", opaque=\"{}\"", opaque));
+        }
+
+        Some(header)
+    }
+
+    fn digest_response(&self, challenge: &DigestChallenge, method: &Method, uri: &str, nc: &str, cnonce: &str) -> String {
+        let mut ha1 = md5_hex(&format!("{}:{}:{}", self.username, challenge.realm, self.password));
+        if challenge.algorithm.eq_ignore_ascii_case("md5-sess") {
+            ha1 = md5_hex(&format!("{}:{}:{}", ha1, challenge.nonce, cnonce));
+        }
+        let ha2 = md5_hex(&format!("{}:{}", method, uri));
+
+        match &challenge.qop {
+            Some(qop) => md5_hex(&format!("{}:{}:{}:{}:{}:{}", ha1, challenge.nonce, nc, cnonce, qop, ha2)),
+            None => md5_hex(&format!("{}:{}:{}", ha1, challenge.nonce, ha2)),
+        }
+    }
+}
+
+fn host_of(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    Some(format!("{}:{}", url.host_str()?, url.port_or_known_default()?))
+}
+
+fn md5_hex(text: &str) -> String {
+    format!("{:x}", md5::compute(text))
+}
+
+// Split 'realm="a, b", nonce="xyz", qop=auth' into its parameters. Quoted values can contain
+// commas, so this can't just split on them.
+fn parse_params(text: &str) -> HashMap<String, String> {
+    let mut params = HashMap::new();
+    let mut chars = text.chars().peekable();
+
+    loop {
+        while chars.next_if(|c| c.is_whitespace() || *c == ',').is_some() {}
+
+        let name: String = std::iter::from_fn(|| chars.next_if(|c| *c != '=' && *c != ',')).collect();
+        if name.trim().is_empty() {
+            break;
+        }
+
+        let mut value = String::new();
+        if chars.next_if_eq(&'=').is_some() {
+            if chars.next_if_eq(&'"').is_some() {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' => value.extend(chars.next()),
+                        '"' => break,
+                        c => value.push(c),
+                    }
+                }
+            } else {
+                value = std::iter::from_fn(|| chars.next_if(|c| *c != ',')).collect();
+            }
+        }
+
+        params.insert(name.trim().to_lowercase(), value.trim().to_string());
+    }
+
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    // The MD5 example from RFC 7616 section 3.9.1
+    const RFC_CHALLENGE: &str = "Digest realm=\"http-auth@example.org\", qop=\"auth, auth-int\", algorithm=MD5, \
+        nonce=\"7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v\", opaque=\"FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS\"";
+
+    fn rfc_challenge() -> DigestChallenge {
+        let mut headers = HeaderMap::new();
+        headers.insert(WWW_AUTHENTICATE, HeaderValue::from_static(RFC_CHALLENGE));
+        let (challenge, stale) = DigestChallenge::parse(&headers).unwrap();
+        assert!(!stale);
+        challenge
+    }
+
+    #[test]
+    fn parses_rfc_challenge() {
+        let challenge = rfc_challenge();
+        assert_eq!(challenge.realm, "http-auth@example.org");
+        assert_eq!(challenge.nonce, "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v");
+        assert_eq!(challenge.opaque.as_deref(), Some("FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS"));
+        assert_eq!(challenge.algorithm, "MD5");
+        assert_eq!(challenge.qop.as_deref(), Some("auth"));
+    }
+
+    #[test]
+    fn md5_response_matches_rfc() {
+        let auth = Auth::new(AuthType::Digest, "Mufasa:Circle of Life").unwrap();
+        let response = auth.digest_response(
+            &rfc_challenge(),
+            &Method::GET,
+            "/dir/index.html",
+            "00000001",
+            "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ",
+        );
+        assert_eq!(response, "8ca523f5e9506fed4657c9700eebdbec");
+    }
+
+    #[test]
+    fn quoted_params_keep_commas_and_escapes() {
+        let params = parse_params(r#"realm="a, \"b\"", Nonce=xyz ,stale=TRUE, empty="""#);
+        assert_eq!(params["realm"], r#"a, "b""#);
+        assert_eq!(params["nonce"], "xyz");
+        assert_eq!(params["stale"], "TRUE");
+        assert_eq!(params["empty"], "");
+    }
+}
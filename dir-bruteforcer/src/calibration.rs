@@ -0,0 +1,209 @@
+use crate::response::ScanResponse;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use reqwest::Url;
+
+// Placeholder swapped in for the requested word when comparing redirect targets, so a catch-all
+// that redirects /<anything> to /<anything>/ produces the same fingerprint for every word
+const WORD_PLACEHOLDER: &str = "FUZZ";
+
+// How a "page not found" looks on a target that doesn't answer with a plain 404
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Fingerprint {
+    pub status: u16,
+    pub size: usize,
+    pub words: usize,
+    pub lines: usize,
+    pub redirect: Option<String>,
+    pub word_length: usize,
+    // Bytes added to the page per character of the word, worked out from two probes of different
+    // lengths. None until that's been seen, in which case only an exact size match counts.
+    pub reflection: Option<i64>,
+}
+
+impl Fingerprint {
+    pub fn new(response: &ScanResponse, word: &str) -> Self {
+        Fingerprint {
+            status: response.status,
+            size: response.size,
+            words: response.words,
+            lines: response.lines,
+            redirect: response.redirect.as_ref().map(|location| mask_word(location, word)),
+            word_length: word.len(),
+            reflection: None,
+        }
+    }
+
+    // Same page apart from the size, which could still be the same catch-all echoing the word back
+    fn same_shape(&self, other: &Fingerprint) -> bool {
+        self.status == other.status
+            && self.redirect == other.redirect
+            && self.words == other.words
+            && self.lines == other.lines
+    }
+
+    // How many bytes per character of the word would explain the size difference, if any
+    fn reflection_rate(&self, other: &Fingerprint) -> Option<i64> {
+        let length_difference = other.word_length as i64 - self.word_length as i64;
+        let size_difference = other.size as i64 - self.size as i64;
+
+        if length_difference == 0 || size_difference % length_difference != 0 {
+            return None;
+        }
+
+        Some(size_difference / length_difference).filter(|rate| *rate > 0)
+    }
+
+    // Pages that reflect the requested path change size with the word length, so a different size
+    // is only accepted when it's exactly what the probes showed the reflection adds
+    fn matches(&self, other: &Fingerprint) -> bool {
+        if !self.same_shape(other) {
+            return false;
+        }
+
+        if self.size == other.size {
+            return true;
+        }
+
+        self.reflection.is_some() && self.reflection_rate(other) == self.reflection
+    }
+}
+
+// Swap the word for a placeholder in the redirect's path, so a catch-all that redirects
+// /<anything> to /<anything>/ gives the same fingerprint for every word. Only whole path segments
+// are replaced, a word like "www" or "api" can also be part of the host.
+fn mask_word(location: &str, word: &str) -> String {
+    let word = word.trim_matches('/');
+    if word.is_empty() {
+        return location.to_string();
+    }
+
+    let Ok(mut url) = Url::parse(location) else {
+        return location.to_string();
+    };
+
+    let path = if word.contains('/') {
+        url.path().replace(word, WORD_PLACEHOLDER)
+    } else {
+        url.path()
+            .split('/')
+            .map(|segment| if segment == word { WORD_PLACEHOLDER } else { segment })
+            .collect::<Vec<&str>>()
+            .join("/")
+    };
+    url.set_path(&path);
+
+    url.to_string()
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Calibration {
+    pub fingerprints: Vec<Fingerprint>,
+}
+
+impl Calibration {
+    // Random paths that shouldn't exist on any target. A mix of file and directory style names is
+    // used since some servers only wildcard one of them.
+    pub fn probe_words() -> Vec<String> {
+        vec![
+            random_word(12),
+            random_word(20),
+            format!("{}/", random_word(12)),
+            format!("{}.html", random_word(12)),
+        ]
+    }
+
+    // A probe that looks like one we already have, apart from a size difference that lines up with
+    // the word length, tells us how much of the word the page reflects
+    pub fn add(&mut self, response: &ScanResponse, word: &str) {
+        let fingerprint = Fingerprint::new(response, word);
+
+        for known in self.fingerprints.iter_mut() {
+            if known.reflection.is_none() && known.same_shape(&fingerprint) {
+                if let Some(rate) = known.reflection_rate(&fingerprint) {
+                    known.reflection = Some(rate);
+                    return;
+                }
+            }
+        }
+
+        if !self.fingerprints.iter().any(|known| known.matches(&fingerprint)) {
+            self.fingerprints.push(fingerprint);
+        }
+    }
+
+    pub fn is_wildcard(&self, response: &ScanResponse, word: &str) -> bool {
+        let fingerprint = Fingerprint::new(response, word);
+        self.fingerprints.iter().any(|known| known.matches(&fingerprint))
+    }
+}
+
+pub fn random_word(length: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(length)
+        .map(char::from)
+        .collect::<String>()
+        .to_lowercase()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn response(size: usize, words: usize, redirect: Option<&str>) -> ScanResponse {
+        ScanResponse {
+            url: String::new(),
+            word: String::new(),
+            status: 200,
+            size,
+            words,
+            lines: 1,
+            redirect: redirect.map(String::from),
+            content_type: None,
+            duration: Duration::ZERO,
+            flags: Vec::new(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+    }
+
+    // A catch-all that echoes the word once: 48 bytes plus the word
+    fn reflecting_calibration() -> Calibration {
+        let mut calibration = Calibration::default();
+        calibration.add(&response(60, 8, None), "abcdefghijkl");
+        calibration.add(&response(68, 8, None), "abcdefghijklmnopqrst");
+        calibration
+    }
+
+    #[test]
+    fn reflected_word_is_wildcard() {
+        let calibration = reflecting_calibration();
+        assert_eq!(calibration.fingerprints.len(), 1);
+        assert!(calibration.is_wildcard(&response(61, 8, None), "administrator"));
+        assert!(calibration.is_wildcard(&response(53, 8, None), "login"));
+    }
+
+    #[test]
+    fn larger_page_with_same_word_count_is_not_wildcard() {
+        let calibration = reflecting_calibration();
+        assert!(!calibration.is_wildcard(&response(83, 8, None), "administrator"));
+    }
+
+    #[test]
+    fn size_change_without_known_reflection_is_not_wildcard() {
+        let mut calibration = Calibration::default();
+        calibration.add(&response(60, 8, None), "abcdefghijkl");
+        assert!(!calibration.is_wildcard(&response(83, 8, None), "administrator"));
+        assert!(calibration.is_wildcard(&response(60, 8, None), "administrator"));
+    }
+
+    #[test]
+    fn redirect_only_masks_the_requested_segment() {
+        let mut calibration = Calibration::default();
+        calibration.add(&response(0, 0, Some("http://www.example.com/abcdefghijkl/")), "abcdefghijkl");
+        assert!(calibration.is_wildcard(&response(0, 0, Some("http://www.example.com/www/")), "www"));
+        assert!(!calibration.is_wildcard(&response(0, 0, Some("http://www.example.com/login")), "www"));
+    }
+}
@@ -0,0 +1,122 @@
+use clap::ValueEnum;
+use reqwest::redirect::Policy;
+use reqwest::{Client, Identity, Proxy, Url};
+use std::error::Error;
+use std::fs;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::time::Duration;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+pub enum HttpVersion {
+    /// Let the server decide (HTTP/2 over TLS when offered, HTTP/1.1 otherwise)
+    Auto,
+    /// Only speak HTTP/1.1
+    Http1,
+    /// Assume the server speaks HTTP/2 without negotiating it first
+    Http2,
+}
+
+// Connection settings for the single client shared by every request in a scan
+#[derive(Clone, Debug)]
+pub struct ClientOptions {
+    pub timeout: Duration,
+    pub pool_size: Option<usize>,
+    pub idle_timeout: Duration,
+    pub tcp_nodelay: bool,
+    pub http_version: HttpVersion,
+    pub proxy: Option<String>,
+    pub insecure: bool,
+    pub client_cert: Option<String>,
+    pub client_key: Option<String>,
+    pub resolve: Option<(String, SocketAddr)>,
+    pub follow_redirects: Option<usize>,
+}
+
+// The same defaults as the command line
+impl Default for ClientOptions {
+    fn default() -> Self {
+        ClientOptions {
+            timeout: Duration::from_secs(10),
+            pool_size: None,
+            idle_timeout: Duration::from_secs(90),
+            tcp_nodelay: true,
+            http_version: HttpVersion::Auto,
+            proxy: None,
+            insecure: false,
+            client_cert: None,
+            client_key: None,
+            resolve: None,
+            follow_redirects: None,
+        }
+    }
+}
+
+// Reusing one client keeps connections alive between requests instead of paying for a new
+// TCP/TLS handshake every time like reqwest::get does
+pub fn build_client(options: &ClientOptions) -> Result<Client, Box<dyn Error>> {
+    let mut builder = Client::builder()
+        .timeout(options.timeout)
+        .pool_idle_timeout(options.idle_timeout)
+        .tcp_nodelay(options.tcp_nodelay);
+
+    if let Some(pool_size) = options.pool_size {
+        builder = builder.pool_max_idle_per_host(pool_size);
+    }
+
+    builder = match options.http_version {
+        HttpVersion::Auto => builder,
+        HttpVersion::Http1 => builder.http1_only(),
+        HttpVersion::Http2 => builder.http2_prior_knowledge(),
+    };
+
+    // http://, https:// and socks5:// (or socks5h:// to resolve names on the proxy) are all accepted
+    if let Some(proxy) = &options.proxy {
+        builder = builder.proxy(Proxy::all(proxy)?);
+    }
+
+    // Not following redirects is what lets us see the Location header, and the real status code
+    builder = match options.follow_redirects {
+        Some(max_hops) => builder.redirect(Policy::limited(max_hops)),
+        None => builder.redirect(Policy::none()),
+    };
+
+    if options.insecure {
+        builder = builder
+            .danger_accept_invalid_certs(true)
+            .danger_accept_invalid_hostnames(true);
+    }
+
+    // Client certificates for mTLS, both given as PEM files with the key in PKCS#8 format
+    match (&options.client_cert, &options.client_key) {
+        (Some(cert), Some(key)) => {
+            let identity = Identity::from_pkcs8_pem(&fs::read(cert)?, &fs::read(key)?)?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => return Err("a client certificate and key have to be given together".into()),
+    }
+
+    if let Some((host, address)) = &options.resolve {
+        builder = builder.resolve(host, *address);
+    }
+
+    Ok(builder.build()?)
+}
+
+// Point the target URL at `host` while still connecting to the address it originally named, so the
+// SNI and Host header say `host` but the connection goes to the original IP. Returns the rewritten
+// URL along with the name/address pair the client needs to resolve it.
+pub fn override_host(url: &str, host: &str) -> Result<(String, (String, SocketAddr)), Box<dyn Error>> {
+    let mut url = Url::parse(url)?;
+
+    let original_host = url.host_str().ok_or("the target URL has no host")?.to_string();
+    let port = url.port_or_known_default().ok_or("the target URL has no port")?;
+    let address = (original_host.as_str(), port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| format!("could not resolve {}", original_host))?;
+
+    url.set_host(Some(host))?;
+
+    Ok((url.to_string(), (host.to_string(), address)))
+}
@@ -0,0 +1,122 @@
+use crate::progress::Progress;
+use colored::Colorize;
+use dir_bruteforcer::filters::StatusCategory;
+use dir_bruteforcer::{ResultSink, ScanResult};
+use std::collections::HashMap;
+use std::io;
+use std::sync::Mutex;
+
+// Where the CLI's output goes. With several targets being scanned at once the lines are held back
+// and printed together when the target finishes, so the output stays grouped per target.
+pub struct Console {
+    grouped: bool,
+    lines: Mutex<HashMap<String, Vec<String>>>,
+    progress: Progress,
+    vhost: Option<String>,
+    show_match: bool,
+}
+
+impl Console {
+    pub fn new(grouped: bool, progress: Progress, vhost: Option<String>, show_match: bool) -> Self {
+        Console {
+            grouped,
+            lines: Mutex::new(HashMap::new()),
+            progress,
+            vhost,
+            show_match,
+        }
+    }
+
+    fn println(&self, target: &str, line: String) {
+        if self.grouped {
+            self.lines.lock().unwrap().entry(target.to_string()).or_default().push(line);
+        } else {
+            self.progress.println(&line);
+        }
+    }
+
+    // Lines are taken out as they're printed, so a target is never shown twice
+    fn flush(&self, target: &str) {
+        if !self.grouped {
+            return;
+        }
+
+        let lines = self.lines.lock().unwrap().remove(target).unwrap_or_default();
+        let mut block = format!("=== {} ({} line(s)) ===", target, lines.len()).bold().to_string();
+        for line in lines.iter() {
+            block.push('\n');
+            block.push_str(line);
+        }
+        block.push('\n');
+        self.progress.println(&block);
+    }
+
+    // Whatever the unfinished targets had found so far still gets shown when a scan is interrupted
+    pub fn flush_all(&self) {
+        let mut targets: Vec<String> = self.lines.lock().unwrap().keys().cloned().collect();
+        targets.sort();
+        for target in targets {
+            self.flush(&target);
+        }
+    }
+}
+
+impl ResultSink for Console {
+    fn result(&self, result: &ScanResult) -> io::Result<()> {
+        let response = &result.response;
+
+        // Every vhost result has the same URL, the host name is what's interesting
+        let label = match &self.vhost {
+            Some(domain) => format!("{}.{}", response.word, domain.trim_start_matches('.')),
+            None => response.url.clone(),
+        };
+
+        let category = StatusCategory::from_status(response.status);
+        let mut line = format!(
+            "{} - {} - Size: {}, Words: {}, Lines: {}, Time: {}ms",
+            label,
+            category.colourize(&response.status.to_string()),
+            response.size,
+            response.words,
+            response.lines,
+            response.duration.as_millis()
+        );
+
+        if let Some(redirect) = &response.redirect {
+            line = format!("{} -> {}", line, redirect);
+        }
+
+        for flag in &response.flags {
+            line = format!("{} [{}]", line, flag.yellow());
+        }
+
+        if self.show_match {
+            if let Some(matched) = &result.matched {
+                line = format!("{}\n    {} {}", line, "match:".dimmed(), matched);
+            }
+        }
+
+        self.println(&result.target, line);
+        Ok(())
+    }
+
+    fn info(&self, target: &str, message: &str) {
+        self.println(target, message.to_string());
+    }
+
+    fn error(&self, message: &str) {
+        self.progress.eprintln(message);
+    }
+
+    fn requests_queued(&self, count: usize) {
+        self.progress.add_total(count);
+    }
+
+    fn request_done(&self, status: Option<u16>) {
+        self.progress.record(status);
+    }
+
+    fn target_finished(&self, target: &str) {
+        self.flush(target);
+    }
+}
@@ -0,0 +1,144 @@
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum ErrorKind {
+    Timeout,
+    Connect,
+    Tls,
+    Body,
+    Other,
+}
+
+impl ErrorKind {
+    pub fn classify(error: &reqwest::Error) -> Self {
+        // Only the sources, reqwest's own message has the URL in it and that could be anything
+        let chain = error.source().map(error_chain).unwrap_or_default().to_lowercase();
+
+        // TLS failures show up as connect errors, so the source chain has to be checked for them first
+        if ["certificate", "tls", "ssl", "handshake"].iter().any(|s| chain.contains(s)) {
+            ErrorKind::Tls
+        } else if error.is_timeout() {
+            ErrorKind::Timeout
+        } else if error.is_connect() || has_io_source(error) || chain.contains("connection") {
+            // Resets and early closes happen after connecting, but are still connection problems
+            ErrorKind::Connect
+        } else if error.is_body() || error.is_decode() {
+            ErrorKind::Body
+        } else {
+            ErrorKind::Other
+        }
+    }
+
+    // Failures that might go away if the same request is sent again
+    pub fn is_retryable(&self) -> bool {
+        matches!(self, ErrorKind::Timeout | ErrorKind::Connect | ErrorKind::Body)
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::Connect => "connect",
+            ErrorKind::Tls => "tls",
+            ErrorKind::Body => "body",
+            ErrorKind::Other => "other",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+// reqwest's own messages are fairly vague ("error sending request"), the useful part is usually
+// further down the source chain
+fn error_chain(error: &dyn Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(inner) = source {
+        message = format!("{}: {}", message, inner);
+        source = inner.source();
+    }
+    message
+}
+
+fn has_io_source(error: &dyn Error) -> bool {
+    let mut source = error.source();
+    while let Some(inner) = source {
+        if inner.is::<std::io::Error>() {
+            return true;
+        }
+        source = inner.source();
+    }
+    false
+}
+
+#[derive(Debug)]
+pub struct RequestError {
+    pub url: String,
+    pub kind: ErrorKind,
+    pub source: reqwest::Error,
+}
+
+impl RequestError {
+    pub fn new(url: &str, source: reqwest::Error) -> Self {
+        RequestError {
+            url: url.to_string(),
+            kind: ErrorKind::classify(&source),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for RequestError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} error for {}: {}", self.kind, self.url, error_chain(&self.source))
+    }
+}
+
+impl Error for RequestError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+// Counts of failed requests by kind, shared between all the request tasks
+#[derive(Debug, Default)]
+pub struct ErrorStats {
+    counts: Mutex<BTreeMap<ErrorKind, usize>>,
+    retries: AtomicUsize,
+}
+
+impl ErrorStats {
+    pub fn record(&self, error: &RequestError) {
+        let mut counts = self.counts.lock().unwrap();
+        *counts.entry(error.kind).or_insert(0) += 1;
+    }
+
+    pub fn record_retry(&self) {
+        self.retries.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn print_summary(&self) {
+        let counts = self.counts.lock().unwrap();
+        let retries = self.retries.load(Ordering::Relaxed);
+        if counts.is_empty() && retries == 0 {
+            return;
+        }
+
+        if counts.is_empty() {
+            println!("[*] {} request(s) retried, none failed", retries);
+            return;
+        }
+
+        let total: usize = counts.values().sum();
+        let breakdown: Vec<String> = counts
+            .iter()
+            .map(|(kind, count)| format!("{}: {}", kind, count))
+            .collect();
+
+        println!("[-] {} request(s) failed ({}), {} retried", total, breakdown.join(", "), retries);
+    }
+}
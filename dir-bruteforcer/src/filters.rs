@@ -0,0 +1,306 @@
+use crate::response::ScanResponse;
+use colored::{ColoredString, Colorize};
+use regex::Regex;
+use std::fmt;
+use std::ops::RangeInclusive;
+use std::str::FromStr;
+use std::time::Duration;
+
+// List of numbers and inclusive ranges, parsed from input like "200-299,401,403"
+#[derive(Clone, Debug, Default)]
+pub struct RangeList {
+    ranges: Vec<RangeInclusive<u64>>,
+}
+
+impl RangeList {
+    pub fn contains(&self, value: u64) -> bool {
+        self.ranges.iter().any(|range| range.contains(&value))
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+impl FromStr for RangeList {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut ranges = Vec::new();
+
+        for part in s.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let range = match part.split_once('-') {
+                Some((start, end)) => {
+                    let start = parse_number(start)?;
+                    let end = parse_number(end)?;
+                    if start > end {
+                        return Err(format!("invalid range '{}': start is greater than end", part));
+                    }
+                    start..=end
+                }
+                None => {
+                    let value = parse_number(part)?;
+                    value..=value
+                }
+            };
+            ranges.push(range);
+        }
+
+        Ok(RangeList { ranges })
+    }
+}
+
+impl fmt::Display for RangeList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let parts: Vec<String> = self
+            .ranges
+            .iter()
+            .map(|range| {
+                if range.start() == range.end() {
+                    range.start().to_string()
+                } else {
+                    format!("{}-{}", range.start(), range.end())
+                }
+            })
+            .collect();
+
+        write!(f, "{}", parts.join(","))
+    }
+}
+
+// A response time limit in milliseconds, given as ">500" or "<500"
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimeThreshold {
+    Above(u64),
+    Below(u64),
+}
+
+impl TimeThreshold {
+    pub fn contains(&self, duration: Duration) -> bool {
+        let millis = duration.as_millis() as u64;
+        match self {
+            TimeThreshold::Above(limit) => millis > *limit,
+            TimeThreshold::Below(limit) => millis < *limit,
+        }
+    }
+}
+
+impl FromStr for TimeThreshold {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if let Some(limit) = s.strip_prefix('>') {
+            Ok(TimeThreshold::Above(parse_number(limit)?))
+        } else if let Some(limit) = s.strip_prefix('<') {
+            Ok(TimeThreshold::Below(parse_number(limit)?))
+        } else {
+            Err(format!("invalid time '{}', expected e.g. '>500' or '<100' (milliseconds)", s))
+        }
+    }
+}
+
+impl fmt::Display for TimeThreshold {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TimeThreshold::Above(limit) => write!(f, ">{}", limit),
+            TimeThreshold::Below(limit) => write!(f, "<{}", limit),
+        }
+    }
+}
+
+fn floor_char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+fn ceil_char_boundary(s: &str, mut index: usize) -> usize {
+    while !s.is_char_boundary(index) {
+        index += 1;
+    }
+    index
+}
+
+fn parse_number(s: &str) -> Result<u64, String> {
+    s.trim()
+        .parse::<u64>()
+        .map_err(|_| format!("'{}' is not a valid number", s.trim()))
+}
+
+// Everything a response has to get through before it's reported. Match lists other than the
+// status codes are only checked when they've been given, and any filter that hits hides the response.
+#[derive(Clone, Debug)]
+pub struct Filters {
+    pub match_codes: RangeList,
+    pub filter_codes: RangeList,
+    pub match_size: RangeList,
+    pub filter_size: RangeList,
+    pub match_words: RangeList,
+    pub filter_words: RangeList,
+    pub match_lines: RangeList,
+    pub filter_lines: RangeList,
+    pub match_time: Option<TimeThreshold>,
+    pub filter_time: Option<TimeThreshold>,
+    pub match_regex: Option<Regex>,
+    pub filter_regex: Option<Regex>,
+    pub regex_headers: bool,
+}
+
+// Status codes reported when no --match-codes are given
+pub const DEFAULT_MATCH_CODES: &str = "200-299,301,302,307,308,401,403,405,500";
+
+impl Default for Filters {
+    fn default() -> Self {
+        Filters {
+            match_codes: DEFAULT_MATCH_CODES.parse().expect("default match codes are valid"),
+            filter_codes: RangeList::default(),
+            match_size: RangeList::default(),
+            filter_size: RangeList::default(),
+            match_words: RangeList::default(),
+            filter_words: RangeList::default(),
+            match_lines: RangeList::default(),
+            filter_lines: RangeList::default(),
+            match_time: None,
+            filter_time: None,
+            match_regex: None,
+            filter_regex: None,
+            regex_headers: false,
+        }
+    }
+}
+
+impl Filters {
+    // Just the status code part of the checks, for when there's no body to look at yet
+    pub fn status_matches(&self, status: u16) -> bool {
+        let status = status as u64;
+        self.match_codes.contains(status) && !self.filter_codes.contains(status)
+    }
+
+    pub fn is_match(&self, response: &ScanResponse) -> bool {
+        let status = response.status as u64;
+        let size = response.size as u64;
+        let words = response.words as u64;
+        let lines = response.lines as u64;
+
+        if !self.match_codes.contains(status) {
+            return false;
+        }
+
+        let matched = [
+            (&self.match_size, size),
+            (&self.match_words, words),
+            (&self.match_lines, lines),
+        ]
+        .iter()
+        .all(|(list, value)| list.is_empty() || list.contains(*value));
+
+        let filtered = [
+            (&self.filter_codes, status),
+            (&self.filter_size, size),
+            (&self.filter_words, words),
+            (&self.filter_lines, lines),
+        ]
+        .iter()
+        .any(|(list, value)| list.contains(*value));
+
+        if !matched || filtered {
+            return false;
+        }
+
+        let timed = self.match_time.is_none_or(|threshold| threshold.contains(response.duration))
+            && !self.filter_time.is_some_and(|threshold| threshold.contains(response.duration));
+        if !timed {
+            return false;
+        }
+
+        if self.match_regex.is_none() && self.filter_regex.is_none() {
+            return true;
+        }
+
+        let haystack = self.regex_haystack(response);
+        let regex_matched = self.match_regex.as_ref().is_none_or(|regex| regex.is_match(&haystack));
+        let regex_filtered = self.filter_regex.as_ref().is_some_and(|regex| regex.is_match(&haystack));
+
+        regex_matched && !regex_filtered
+    }
+
+    // The text --match-regex found, with a little context either side, squashed onto one line
+    pub fn snippet(&self, response: &ScanResponse) -> Option<String> {
+        const CONTEXT: usize = 40;
+
+        let regex = self.match_regex.as_ref()?;
+        let haystack = self.regex_haystack(response);
+        let found = regex.find(&haystack)?;
+
+        let start = floor_char_boundary(&haystack, found.start().saturating_sub(CONTEXT));
+        let end = ceil_char_boundary(&haystack, (found.end() + CONTEXT).min(haystack.len()));
+
+        let snippet: Vec<&str> = haystack[start..end].split_whitespace().collect();
+        Some(snippet.join(" "))
+    }
+
+    fn regex_haystack(&self, response: &ScanResponse) -> String {
+        if self.regex_headers {
+            format!("{}\n{}", response.header_text(), response.body_text())
+        } else {
+            response.body_text().to_string()
+        }
+    }
+}
+
+// Rough grouping of status codes so hits can be coloured by what they probably mean
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusCategory {
+    Success,
+    Redirect,
+    Blocked,
+    ClientError,
+    ServerError,
+    Other,
+}
+
+impl StatusCategory {
+    pub fn from_status(status: u16) -> Self {
+        match status {
+            200..=299 => StatusCategory::Success,
+            300..=399 => StatusCategory::Redirect,
+            401 | 403 | 407 => StatusCategory::Blocked,
+            400..=499 => StatusCategory::ClientError,
+            500..=599 => StatusCategory::ServerError,
+            _ => StatusCategory::Other,
+        }
+    }
+
+    pub fn colourize(&self, text: &str) -> ColoredString {
+        match self {
+            StatusCategory::Success => text.green(),
+            StatusCategory::Redirect => text.cyan(),
+            StatusCategory::Blocked => text.yellow(),
+            StatusCategory::ClientError => text.magenta(),
+            StatusCategory::ServerError => text.red(),
+            StatusCategory::Other => text.normal(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_list_parses_numbers_and_ranges() {
+        let list: RangeList = "200-299, 401,403,".parse().unwrap();
+        assert!(list.contains(200) && list.contains(299) && list.contains(403));
+        assert!(!list.contains(300) && !list.contains(402));
+        assert_eq!(list.to_string(), "200-299,401,403");
+    }
+
+    #[test]
+    fn range_list_rejects_bad_input() {
+        assert!("300-200".parse::<RangeList>().is_err());
+        assert!("20x".parse::<RangeList>().is_err());
+        assert!("".parse::<RangeList>().unwrap().is_empty());
+    }
+}
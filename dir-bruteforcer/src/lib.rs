@@ -0,0 +1,25 @@
+// The scan engine, so directory discovery can be driven from other tools as well as the CLI.
+//
+// Build a ScanConfig, hand it to a Scanner along with any result sinks, then either `run` it or
+// take the results as a stream with `into_stream`.
+
+pub mod auth;
+pub mod calibration;
+pub mod client;
+pub mod errors;
+pub mod filters;
+pub mod output;
+pub mod request;
+pub mod response;
+pub mod save;
+pub mod scan;
+pub mod state;
+pub mod throttle;
+pub mod timing;
+pub mod wordlist;
+
+pub use filters::Filters;
+pub use request::RequestTemplate;
+pub use response::ScanResponse;
+pub use scan::{ResultSink, ScanConfig, ScanConfigBuilder, ScanResult, Scanner};
+pub use wordlist::{AttackMode, Wordlists};
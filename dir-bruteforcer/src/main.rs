@@ -1,66 +1,277 @@
-use clap::Parser;
-use reqwest;
-use futures::stream::{StreamExt};
-use tokio::task;
-use colored::Colorize;
-use std::fs::File;
+use clap::{ArgAction, Parser};
+use regex::Regex;
+use futures::future;
+use reqwest::Method;
 use std::io::{self, BufRead};
-use std::path::Path;
-use std::error::Error;
-use std::time::{Instant};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+pub mod console;
+pub mod progress;
+
+use console::Console;
+use dir_bruteforcer::auth::{Auth, AuthType};
+use dir_bruteforcer::client::{override_host, ClientOptions, HttpVersion};
+use dir_bruteforcer::filters::{Filters, RangeList, TimeThreshold, DEFAULT_MATCH_CODES};
+use dir_bruteforcer::output::{OutputFormat, OutputWriter, ScanHeader};
+use dir_bruteforcer::request::{build_headers, parse_header, parse_method, RequestTemplate};
+use dir_bruteforcer::save::ResponseSaver;
+use dir_bruteforcer::state::ScanState;
+use dir_bruteforcer::wordlist::{read_lines, AttackMode, WordlistSpec, Wordlists};
+use dir_bruteforcer::{ScanConfig, Scanner};
+use progress::Progress;
 
 // Intended to be a clone of dirbuster/gobuster/feroxbuster as a way of practicing making web requests in rust
-// TODO:
-//  - Add filters for status code, size, and maybe something else
-//  - Add option for custom headers (User agent, etc.)
 
 #[derive(Parser, Debug)]
 #[command(version, about, long_about = None)]
 struct Args {
-    /// IP/URL of the target to scan
-    #[arg(short, long)]
-    url: String,
+    /// IP/URL of the target to scan. FUZZ marks where words go, otherwise they're appended to the URL
+    #[arg(short, long, required_unless_present = "url_file", conflicts_with = "url_file")]
+    url: Option<String>,
 
-    /// Wordlist to use for bruteforcing
-    #[arg(short, long)]
-    wordlist: String,
+    /// File with one target URL per line ('-' reads them from stdin). All targets share the same
+    /// thread and rate limits
+    #[arg(long)]
+    url_file: Option<String>,
+
+    /// Wordlist to use for bruteforcing, optionally bound to its own keyword (e.g. users.txt:USER).
+    /// Can be given multiple times, defaults to the FUZZ keyword
+    #[arg(short, long = "wordlist", required = true)]
+    wordlists: Vec<WordlistSpec>,
+
+    /// Virtual host discovery: keep the URL fixed and send WORD.<DOMAIN> as the Host header
+    #[arg(long, value_name = "DOMAIN")]
+    vhost: Option<String>,
+
+    /// How words from multiple wordlists are combined
+    #[arg(long, value_enum, default_value_t = AttackMode::Clusterbomb)]
+    mode: AttackMode,
+
+    /// Extensions to append to every word in the first wordlist (e.g. php,aspx,bak,txt)
+    #[arg(short = 'x', long, value_delimiter = ',')]
+    extensions: Vec<String>,
+
+    /// Also request each word without an extension when using -x
+    #[arg(long)]
+    bare: bool,
+
+    /// Extra header to send with every request, can be given multiple times (e.g. -H 'Authorization: Bearer xyz')
+    #[arg(short = 'H', long = "header", value_parser = parse_header)]
+    headers: Vec<(String, String)>,
+
+    /// Cookies to send with every request (e.g. 'session=abc; theme=dark'), can be given multiple times
+    #[arg(short, long = "cookie")]
+    cookies: Vec<String>,
+
+    /// User agent to send with every request
+    #[arg(short = 'a', long)]
+    user_agent: Option<String>,
+
+    /// HTTP method to use (GET, HEAD, POST, PUT, OPTIONS or anything custom). Defaults to GET, or
+    /// POST when --data is given
+    #[arg(short = 'X', long, value_parser = parse_method)]
+    method: Option<Method>,
+
+    /// Body to send with every request. FUZZ can be used in the body, headers and cookies as well
+    /// as the URL
+    #[arg(long)]
+    data: Option<String>,
+
+    /// Content type of the --data body
+    #[arg(long, default_value = "application/x-www-form-urlencoded")]
+    content_type: String,
+
+    /// Send a HEAD request first and only follow up with the full request when the status code matches
+    #[arg(long)]
+    head_first: bool,
 
     /// Number of threads to use
     #[arg(short, long, default_value = "10")]
     threads: usize,
 
-    /// Enable Debug logging
+    /// Maximum number of requests per second across all threads (defaults to no limit, as does 0)
+    #[arg(long)]
+    rate: Option<u32>,
+
+    /// Milliseconds each thread waits after a request before starting its next one
+    #[arg(long, default_value = "0")]
+    delay: u64,
+
+    /// Seconds to wait for a response before giving up on a request
+    #[arg(long, default_value = "10")]
+    timeout: u64,
+
+    /// How many times to retry a request that failed with a timeout, connection or body error
+    #[arg(long, default_value = "0")]
+    retries: u32,
+
+    /// Milliseconds to wait before the first retry, doubled for every retry after that
+    #[arg(long, default_value = "500")]
+    retry_backoff: u64,
+
+    /// Follow redirects instead of reporting them as they are
+    #[arg(long)]
+    follow_redirects: bool,
+
+    /// Maximum number of redirects to follow with --follow-redirects
+    #[arg(long, default_value = "10")]
+    max_redirects: usize,
+
+    /// Maximum number of idle connections kept open per host (defaults to no limit)
+    #[arg(long)]
+    pool_size: Option<usize>,
+
+    /// Seconds an idle pooled connection is kept alive before being closed
+    #[arg(long, default_value = "90")]
+    idle_timeout: u64,
+
+    /// Disable Nagle's algorithm on connections
+    #[arg(long, default_value_t = true, action = ArgAction::Set)]
+    tcp_nodelay: bool,
+
+    /// HTTP version to use when talking to the target
+    #[arg(long, value_enum, default_value_t = HttpVersion::Auto)]
+    http_version: HttpVersion,
+
+    /// Send all traffic through this proxy (e.g. http://127.0.0.1:8080 or socks5://127.0.0.1:1080)
+    #[arg(long)]
+    proxy: Option<String>,
+
+    /// Re-send only the requests that matched through this proxy, so hits show up in its history
+    #[arg(long)]
+    replay_proxy: Option<String>,
+
+    /// Accept invalid and self-signed TLS certificates
+    #[arg(short = 'k', long)]
+    insecure: bool,
+
+    /// Client certificate (PEM) for targets that require mTLS
+    #[arg(long, requires = "client_key")]
+    client_cert: Option<String>,
+
+    /// Private key (PKCS#8 PEM) for the client certificate
+    #[arg(long, requires = "client_cert")]
+    client_key: Option<String>,
+
+    /// Credentials as user:pass, or the token for bearer auth
+    #[arg(long)]
+    auth: Option<String>,
+
+    /// How to send --auth. Digest challenges are answered automatically when a 401 comes back.
+    #[arg(long, value_enum, default_value_t = AuthType::Basic, requires = "auth")]
+    auth_type: AuthType,
+
+    /// Connect to the IP in the URL but send this name as the SNI and Host header
+    #[arg(long, conflicts_with = "url_file")]
+    sni: Option<String>,
+
+    /// Status codes to report, as a comma separated list of codes and ranges (e.g. 200-299,401,403)
+    #[arg(long, default_value = DEFAULT_MATCH_CODES)]
+    match_codes: RangeList,
+
+    /// Status codes to hide even if they are in the match list (e.g. 404,429)
+    #[arg(long, default_value = "")]
+    filter_codes: RangeList,
+
+    /// Only report responses with a body size in this list (e.g. 100-200,512)
+    #[arg(long, default_value = "")]
+    match_size: RangeList,
+
+    /// Hide responses with a body size in this list
+    #[arg(long, default_value = "")]
+    filter_size: RangeList,
+
+    /// Only report responses with a word count in this list
+    #[arg(long, default_value = "")]
+    match_words: RangeList,
+
+    /// Hide responses with a word count in this list
+    #[arg(long, default_value = "")]
+    filter_words: RangeList,
+
+    /// Only report responses with a line count in this list
+    #[arg(long, default_value = "")]
+    match_lines: RangeList,
+
+    /// Hide responses with a line count in this list
+    #[arg(long, default_value = "")]
+    filter_lines: RangeList,
+
+    /// Only report responses that took more or less than this many milliseconds (e.g. '>500' or '<100')
+    #[arg(long)]
+    match_time: Option<TimeThreshold>,
+
+    /// Hide responses that took more or less than this many milliseconds (e.g. '>5000')
+    #[arg(long)]
+    filter_time: Option<TimeThreshold>,
+
+    /// Flag responses that take more than this many times the target's median response time as
+    /// slow, and report them even if they look like a wildcard response (0 turns this off)
+    #[arg(long, default_value = "5")]
+    slow_factor: f64,
+
+    /// Only report responses whose body matches this regex (e.g. 'Index of /')
+    #[arg(long)]
+    match_regex: Option<Regex>,
+
+    /// Hide responses whose body matches this regex
+    #[arg(long)]
+    filter_regex: Option<Regex>,
+
+    /// Run --match-regex and --filter-regex against the response headers as well as the body
+    #[arg(long)]
+    regex_headers: bool,
+
+    /// Print the text matched by --match-regex under each result
+    #[arg(long, requires = "match_regex")]
+    show_match: bool,
+
+    /// Skip requesting random paths up front to detect wildcard / soft-404 responses
+    #[arg(long)]
+    no_calibration: bool,
+
+    /// How many directory levels below the target to recurse into (0 disables recursion)
+    #[arg(long, default_value = "2")]
+    depth: usize,
+
+    /// Directories that should never be recursed into, matched against the end of the path (e.g. images,static/css)
+    #[arg(long, value_delimiter = ',')]
+    deny_dir: Vec<String>,
+
+    /// Write results to this file
     #[arg(short, long)]
-    debug: bool,
-}
+    output: Option<String>,
 
-// Create static slice of successful codes to filter for later
-static SUCCESSFUL_HTTP_CODES: &[&str] = &["200", "301", "302"];
-// Potential codes indicating the page might exist, but is blocked or forbidden
-static POTENTIAL_HTTP_CODES: &[&str] = &["403"];
-
-
-// Read lines from a wordlist file
-async fn process_urls_concurrently(url: &str, wordlist: &str, thread_count: usize) -> Result<(), Box<dyn Error>> {
-    if let Ok(lines) = read_lines(wordlist) {
-        // Create a stream of tasks with a concurrency limit for our threads variable
-        // Reference: https://gendignoux.com/blog/2021/04/01/rust-async-streams-futures-part1.html
-        let tasks = futures::stream::iter(
-            lines.flatten().map(|line| {
-                let final_url = url.to_string() + &line;
-                
-                // Spawn async task for each URL
-                async move {
-                    make_request(&final_url).await;
-                }
-            })
-        ).buffer_unordered(thread_count); // Limit concurrency to `concurrency_limit`
+    /// Save the headers and body of every result to a file in this directory, with an index.txt
+    /// listing which file is which URL
+    #[arg(long)]
+    save_responses: Option<String>,
 
-        // Execute all tasks and wait for them to complete
-        tasks.for_each(|_| async {}).await;
-    }
+    /// Format of the output file
+    #[arg(long, value_enum, default_value_t = OutputFormat::Json)]
+    format: OutputFormat,
 
-    Ok(())
+    /// Save the scan's progress to this file every --save-interval seconds, so it can be picked up
+    /// again with --resume
+    #[arg(long)]
+    state_file: Option<String>,
+
+    /// How often to save progress to --state-file, in seconds
+    #[arg(long, default_value = "30")]
+    save_interval: u64,
+
+    /// Carry on with a scan from a state file saved earlier. Run it with the same options as before.
+    #[arg(long)]
+    resume: Option<String>,
+
+    /// Don't show the live progress bar
+    #[arg(long)]
+    no_progress: bool,
+
+    /// Enable Debug logging
+    #[arg(short, long)]
+    debug: bool,
 }
 
 #[tokio::main]
@@ -70,82 +281,310 @@ async fn main() {
 
     let start = Instant::now();
 
-    let url = args.url;
-    let wordlist = args.wordlist;
-    let threads = args.threads;
+    let mut targets = match (&args.url, &args.url_file) {
+        (Some(url), _) => vec![url.clone()],
+        (None, Some(path)) => match read_targets(path) {
+            Ok(targets) => targets,
+            Err(e) => {
+                eprintln!("[-] Failed to read targets from {}: {}", path, e);
+                std::process::exit(1);
+            }
+        },
+        (None, None) => unreachable!("clap requires --url or --url-file"),
+    };
     let debug = args.debug;
+    let filters = Filters {
+        match_codes: args.match_codes,
+        filter_codes: args.filter_codes,
+        match_size: args.match_size,
+        filter_size: args.filter_size,
+        match_words: args.match_words,
+        filter_words: args.filter_words,
+        match_lines: args.match_lines,
+        filter_lines: args.filter_lines,
+        match_time: args.match_time,
+        filter_time: args.filter_time,
+        match_regex: args.match_regex.clone(),
+        filter_regex: args.filter_regex.clone(),
+        regex_headers: args.regex_headers,
+    };
+
+    let headers = build_headers(&args.headers, &args.cookies, args.user_agent.as_deref());
+
+    let mut resolve = None;
+    if let Some(sni) = &args.sni {
+        // --sni can't be combined with --url-file, so there's only the one target here
+        match override_host(&targets[0], sni) {
+            Ok((new_url, pair)) => {
+                targets[0] = new_url;
+                resolve = Some(pair);
+            }
+            Err(e) => {
+                eprintln!("[-] Failed to apply SNI override: {}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
+    let client_options = ClientOptions {
+        timeout: Duration::from_secs(args.timeout),
+        pool_size: args.pool_size,
+        idle_timeout: Duration::from_secs(args.idle_timeout),
+        tcp_nodelay: args.tcp_nodelay,
+        http_version: args.http_version,
+        proxy: args.proxy,
+        insecure: args.insecure,
+        client_cert: args.client_cert,
+        client_key: args.client_key,
+        resolve,
+        follow_redirects: args.follow_redirects.then_some(args.max_redirects),
+    };
+
+    let auth = match &args.auth {
+        Some(credentials) => match Auth::new(args.auth_type, credentials) {
+            Ok(auth) => Some(auth),
+            Err(e) => {
+                eprintln!("[-] Invalid --auth: {}", e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let wordlists = match Wordlists::load(&args.wordlists, args.mode, &args.extensions, args.bare) {
+        Ok(wordlists) => wordlists,
+        Err(e) => {
+            eprintln!("[-] Failed to read wordlist: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let config = ScanConfig::builder(wordlists)
+        .client_options(client_options.clone())
+        .replay_proxy(args.replay_proxy.clone())
+        .auth(auth)
+        .threads(args.threads)
+        .rate(args.rate)
+        .delay(Duration::from_millis(args.delay))
+        .retries(args.retries, Duration::from_millis(args.retry_backoff))
+        .filters(filters)
+        .calibrate(!args.no_calibration)
+        .head_first(args.head_first)
+        .slow_factor(args.slow_factor)
+        .vhost(args.vhost.clone())
+        .depth(args.depth)
+        .deny_dirs(args.deny_dir)
+        .debug(debug)
+        .build();
+
+    let config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("[-] {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    let mut scanner = Scanner::new(config);
+    if let Some(path) = &args.resume {
+        let resumed = ScanState::load(path)
+            .map_err(|e| e.into())
+            .and_then(|state| scanner.with_state(state));
+        scanner = match resumed {
+            Ok(scanner) => scanner,
+            Err(e) => {
+                eprintln!("[-] Can't resume from {}: {}, resume with the same options as before", path, e);
+                std::process::exit(1);
+            }
+        };
+    }
+
+    let config = scanner.config();
+    let filters = &config.filters;
+
+    let output = match &args.output {
+        Some(path) => {
+            let header = ScanHeader {
+                version: env!("CARGO_PKG_VERSION").to_string(),
+                started: SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0),
+                targets: targets.clone(),
+                wordlists: args.wordlists.iter().map(|spec| spec.to_string()).collect(),
+                mode: args.mode,
+                extensions: args.extensions.clone(),
+                threads: args.threads,
+                depth: args.depth,
+                match_codes: filters.match_codes.to_string(),
+                filter_codes: filters.filter_codes.to_string(),
+                match_size: filters.match_size.to_string(),
+                filter_size: filters.filter_size.to_string(),
+                match_words: filters.match_words.to_string(),
+                filter_words: filters.filter_words.to_string(),
+                match_lines: filters.match_lines.to_string(),
+                filter_lines: filters.filter_lines.to_string(),
+                match_time: filters.match_time.map(|threshold| threshold.to_string()),
+                filter_time: filters.filter_time.map(|threshold| threshold.to_string()),
+                match_regex: filters.match_regex.as_ref().map(|regex| regex.to_string()),
+                filter_regex: filters.filter_regex.as_ref().map(|regex| regex.to_string()),
+            };
+            // The output file is written from scratch, so results from before a resume go back in first
+            let writer = OutputWriter::create(path, args.format, header).and_then(|writer| {
+                scanner
+                    .state()
+                    .results()
+                    .into_iter()
+                    .try_for_each(|record| writer.write_record(record))
+                    .map(|_| writer)
+            });
+            match writer {
+                Ok(writer) => Some(Arc::new(writer)),
+                Err(e) => {
+                    eprintln!("[-] Failed to create output file {}: {}", path, e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        None => None,
+    };
+
+    let saver = match &args.save_responses {
+        Some(dir) => match ResponseSaver::create(dir, args.resume.is_some()) {
+            Ok(saver) => Some(Arc::new(saver)),
+            Err(e) => {
+                eprintln!("[-] Failed to set up {} for saving responses: {}", dir, e);
+                std::process::exit(1);
+            }
+        },
+        None => None,
+    };
+
+    let templates: Vec<RequestTemplate> = targets
+        .iter()
+        .map(|target| config.template(target, args.method.clone(), &headers, args.data.clone(), &args.content_type))
+        .collect();
 
     if debug {
         println!();
-        println!("URL: {}", url);
-        println!("Wordlist: {}", wordlist);
-        println!("'Successful' Status codes: {:?}", SUCCESSFUL_HTTP_CODES);
-        println!("Threads: {}", threads);
+        println!("Targets: {:?}", templates.iter().map(|template| template.url.as_str()).collect::<Vec<&str>>());
+        println!("Vhost domain: {:?}", config.vhost);
+        println!("Wordlists: {:?} ({:?}, {} requests per directory)", args.wordlists.iter().map(|spec| spec.to_string()).collect::<Vec<String>>(), args.mode, config.wordlists.len());
+        println!("Extensions: {:?} (bare words: {})", args.extensions, args.bare);
+        println!("Match codes: {}", filters.match_codes);
+        println!("Filter codes: {}", filters.filter_codes);
+        println!("Match size/words/lines: {} / {} / {}", filters.match_size, filters.match_words, filters.match_lines);
+        println!("Filter size/words/lines: {} / {} / {}", filters.filter_size, filters.filter_words, filters.filter_lines);
+        println!("Match/filter time: {:?} / {:?} (slow factor {})", filters.match_time, filters.filter_time, config.slow_factor);
+        println!("Match/filter regex: {:?} / {:?} (headers: {})", args.match_regex, args.filter_regex, args.regex_headers);
+        println!("Client: {:?}", client_options);
+        println!("Replay proxy: {:?}", args.replay_proxy);
+        println!("SNI override: {:?}", args.sni);
+        println!("Auth: {}", if args.auth.is_some() { format!("{:?}", args.auth_type) } else { "none".to_string() });
+        println!("Headers: {:?}", templates[0].headers);
+        println!("Method: {:?} (HEAD first: {})", args.method, config.head_first);
+        println!("Body: {:?}", args.data);
+        println!("Rate limit: {:?} requests/second, delay {:?}", config.rate, config.delay);
+        println!("Retries: {} (backoff {:?})", config.retries, config.retry_backoff);
+        println!("Threads: {}", config.threads);
+        println!("Recursion depth: {}", config.depth);
+        println!("Denied directories: {:?}", config.deny_dirs);
+        println!("Output: {:?} ({:?})", args.output, args.format);
+        println!("Saving responses to: {:?}", args.save_responses);
+        println!("State file: {:?} (every {}s, resuming from {:?})", args.state_file, args.save_interval, args.resume);
+        println!("Progress bar: {}", !args.no_progress);
         println!("Debug Log: {}", debug);
         println!();
     }
 
     
 
-    // Call the async function to process URLs
-    let _ = process_urls_concurrently(&url, &wordlist, threads).await;
+    // Hits go to the terminal and the output file. Targets are scanned side by side, the shared
+    // semaphore and rate limiter keep the total load the same as for a single target.
+    let progress = Progress::new(!args.no_progress);
+    let console = Arc::new(Console::new(templates.len() > 1, progress.clone(), args.vhost.clone(), args.show_match));
+    scanner = scanner.with_sink(console.clone());
+    if let Some(output) = &output {
+        scanner = scanner.with_sink(output.clone());
+    }
+    if let Some(saver) = saver {
+        scanner = scanner.with_sink(saver);
+    }
+    let scanner = &scanner;
 
-    // if let Ok(lines) = read_lines(wordlist) {
-    //     // Consumes the iterator, returns an (Optional) String
-    //     for line in lines.flatten() {
-    //         let final_url = url.clone() + &line;
-            
-    //         make_request(&final_url).await;
-    //     }
-    // }
+    if args.resume.is_some() {
+        println!("[*] Resuming scan, {} result(s) carried over from last time", scanner.state().results().len());
+    }
 
+    // Saving the state on a timer means even a killed scan only loses the last few seconds
+    let state_file = args.state_file.clone().or(args.resume.clone());
+    let autosave = async {
+        match &state_file {
+            Some(path) => {
+                let mut interval = tokio::time::interval(Duration::from_secs(args.save_interval.max(1)));
+                interval.tick().await;
+                loop {
+                    interval.tick().await;
+                    if let Err(e) = scanner.state().save(path) {
+                        progress.eprintln(&format!("[-] Failed to save state to {}: {}", path, e));
+                    }
+                }
+            }
+            None => future::pending().await,
+        }
+    };
 
-    let duration = start.elapsed();
+    let interrupted = tokio::select! {
+        _ = scanner.run(&templates) => false,
+        _ = autosave => false,
+        _ = tokio::signal::ctrl_c() => true,
+    };
 
-    println!("Scan completed in {} seconds", duration.as_secs());
+    progress.finish();
 
-}
+    if interrupted {
+        console.flush_all();
 
-async fn make_request(url: &str) {
-    //println!("URL: {url}");
+        let path = state_file.unwrap_or_else(|| "dir-bruteforcer.state.json".to_string());
+        match scanner.state().save(&path) {
+            Ok(()) => println!("[*] Interrupted, progress saved to {} (continue with --resume {})", path, path),
+            Err(e) => eprintln!("[-] Interrupted, but failed to save state to {}: {}", path, e),
+        }
+    } else if let Some(path) = &state_file {
+        // Every target is marked finished, so resuming from this does nothing
+        if let Err(e) = scanner.state().save(path) {
+            eprintln!("[-] Failed to save state to {}: {}", path, e);
+        }
+    }
 
-    // HTTP requests reference: https://rust-lang-nursery.github.io/rust-cookbook/web/clients/requests.html
-    let res = reqwest::get(url)
-        .await
-        .unwrap();
+    progress.print_summary();
+    scanner.errors().print_summary();
 
-    
-    // Extract the status and headers before consuming the response body
-    let status = res.status();
-    let headers = res.headers().clone();
+    if let Some(output) = &output {
+        if let Err(e) = output.finish() {
+            eprintln!("[-] Failed to finish writing output file: {}", e);
+        }
+    }
 
-    let length = &headers["content-length"].to_str().unwrap();
+    let duration = start.elapsed();
 
-    if SUCCESSFUL_HTTP_CODES.contains(&(status.as_str())) {
-        println!("{} - {} - {}", url, status.as_str().green(), length);
-    } else if POTENTIAL_HTTP_CODES.contains(&(status.as_str())) {
-        println!("{} - {} - {}", url, status.as_str().yellow(), length);
+    if interrupted {
+        println!("Scan interrupted after {} seconds", duration.as_secs());
+    } else {
+        println!("Scan completed in {} seconds", duration.as_secs());
     }
-    
-    // read the body
-    // if status.is_success() {
-    //     let body = res.text()
-    //         .await
-    //         .unwrap(); // Read the body as text
-
-    //     println!("Status: {}", status);
-    //     //println!("Body:\n{}", body);
-    // } else {
-    //     println!("Failed to fetch the URL. Status: {}", status);
-    // }
+
 }
 
+// Targets from a file, or stdin when the path is '-'. Blank lines and # comments are skipped.
+fn read_targets(path: &str) -> io::Result<Vec<String>> {
+    let lines: Vec<String> = if path == "-" {
+        io::stdin().lock().lines().collect::<io::Result<_>>()?
+    } else {
+        read_lines(path)?.collect::<io::Result<_>>()?
+    };
 
-// Reference: https://doc.rust-lang.org/rust-by-example/std_misc/file/read_lines.html
-// The output is wrapped in a Result to allow matching on errors.
-// Returns an Iterator to the Reader of the lines of the file.
-fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
-where P: AsRef<Path>, {
-    let file = File::open(filename)?;
-    Ok(io::BufReader::new(file).lines())
+    Ok(lines
+        .iter()
+        .map(|line| line.trim())
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(String::from)
+        .collect())
 }
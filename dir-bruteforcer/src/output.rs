@@ -0,0 +1,197 @@
+use crate::response::ScanResponse;
+use crate::scan::{ResultSink, ScanResult};
+use crate::wordlist::AttackMode;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::sync::Mutex;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// One JSON document with the scan parameters and all results, written when the scan ends
+    Json,
+    /// The scan parameters on the first line, then one result per line as they're found
+    Jsonl,
+    /// Comma separated results, with the scan parameters as '#' comment lines at the top
+    Csv,
+}
+
+// The scan parameters written at the top of every output file
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ScanHeader {
+    pub version: String,
+    pub started: u64,
+    pub targets: Vec<String>,
+    pub wordlists: Vec<String>,
+    pub mode: AttackMode,
+    pub extensions: Vec<String>,
+    pub threads: usize,
+    pub depth: usize,
+    pub match_codes: String,
+    pub filter_codes: String,
+    pub match_size: String,
+    pub filter_size: String,
+    pub match_words: String,
+    pub filter_words: String,
+    pub match_lines: String,
+    pub filter_lines: String,
+    pub match_time: Option<String>,
+    pub filter_time: Option<String>,
+    pub match_regex: Option<String>,
+    pub filter_regex: Option<String>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct ResultRecord {
+    pub url: String,
+    pub word: String,
+    pub status: u16,
+    pub size: usize,
+    pub words: usize,
+    pub lines: usize,
+    pub duration_ms: u128,
+    pub redirect: Option<String>,
+    pub content_type: Option<String>,
+    pub flags: Vec<String>,
+}
+
+impl From<&ScanResponse> for ResultRecord {
+    fn from(response: &ScanResponse) -> Self {
+        ResultRecord {
+            url: response.url.clone(),
+            word: response.word.clone(),
+            status: response.status,
+            size: response.size,
+            words: response.words,
+            lines: response.lines,
+            duration_ms: response.duration.as_millis(),
+            redirect: response.redirect.clone(),
+            content_type: response.content_type.clone(),
+            flags: response.flags.clone(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct JsonDocument<'a> {
+    scan: &'a ScanHeader,
+    results: &'a [ResultRecord],
+}
+
+const CSV_COLUMNS: &[&str] = &[
+    "url", "word", "status", "size", "words", "lines", "duration_ms", "redirect", "content_type", "flags",
+];
+
+// Writes hits to a file as they come in. JSON needs the whole list before it can be written, so
+// those results are kept in memory until `finish`.
+#[derive(Debug)]
+pub struct OutputWriter {
+    format: OutputFormat,
+    header: ScanHeader,
+    file: Mutex<BufWriter<File>>,
+    results: Mutex<Vec<ResultRecord>>,
+}
+
+impl OutputWriter {
+    pub fn create(path: &str, format: OutputFormat, header: ScanHeader) -> io::Result<Self> {
+        let mut file = BufWriter::new(File::create(path)?);
+
+        match format {
+            OutputFormat::Json => {}
+            OutputFormat::Jsonl => {
+                serde_json::to_writer(&mut file, &header)?;
+                writeln!(file)?;
+            }
+            OutputFormat::Csv => {
+                let value = serde_json::to_value(&header)?;
+                if let Some(fields) = value.as_object() {
+                    for (key, value) in fields {
+                        writeln!(file, "# {}: {}", key, value)?;
+                    }
+                }
+                writeln!(file, "{}", CSV_COLUMNS.join(","))?;
+            }
+        }
+        file.flush()?;
+
+        Ok(OutputWriter {
+            format,
+            header,
+            file: Mutex::new(file),
+            results: Mutex::new(Vec::new()),
+        })
+    }
+
+    pub fn write(&self, response: &ScanResponse) -> io::Result<()> {
+        self.write_record(ResultRecord::from(response))
+    }
+
+    pub fn write_record(&self, record: ResultRecord) -> io::Result<()> {
+        match self.format {
+            OutputFormat::Json => {
+                self.results.lock().unwrap().push(record);
+                return Ok(());
+            }
+            OutputFormat::Jsonl => {
+                let mut file = self.file.lock().unwrap();
+                serde_json::to_writer(&mut *file, &record)?;
+                writeln!(file)?;
+                file.flush()?;
+            }
+            OutputFormat::Csv => {
+                let fields = [
+                    record.url,
+                    record.word,
+                    record.status.to_string(),
+                    record.size.to_string(),
+                    record.words.to_string(),
+                    record.lines.to_string(),
+                    record.duration_ms.to_string(),
+                    record.redirect.unwrap_or_default(),
+                    record.content_type.unwrap_or_default(),
+                    record.flags.join(";"),
+                ];
+                let line: Vec<String> = fields.iter().map(|field| csv_escape(field)).collect();
+
+                let mut file = self.file.lock().unwrap();
+                writeln!(file, "{}", line.join(","))?;
+                file.flush()?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn finish(&self) -> io::Result<()> {
+        let mut file = self.file.lock().unwrap();
+
+        if self.format == OutputFormat::Json {
+            let results = self.results.lock().unwrap();
+            let document = JsonDocument {
+                scan: &self.header,
+                results: &results,
+            };
+            serde_json::to_writer_pretty(&mut *file, &document)?;
+            writeln!(file)?;
+        }
+
+        file.flush()
+    }
+}
+
+// Quote a CSV field if it contains anything that would break the row apart
+fn csv_escape(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+impl ResultSink for OutputWriter {
+    fn result(&self, result: &ScanResult) -> io::Result<()> {
+        self.write(&result.response)
+    }
+}
@@ -0,0 +1,92 @@
+use indicatif::{ProgressBar, ProgressDrawTarget, ProgressStyle};
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+
+// Live status line shown under the results while a scan runs. The total grows as directories are
+// found, so the ETA is only ever for the work that's known about so far.
+#[derive(Clone)]
+pub struct Progress {
+    bar: ProgressBar,
+    started: Instant,
+    statuses: Arc<Mutex<BTreeMap<u16, usize>>>,
+    errors: Arc<AtomicUsize>,
+}
+
+impl Progress {
+    // The bar draws to stderr and hides itself when that isn't a terminal
+    pub fn new(enabled: bool) -> Self {
+        let bar = ProgressBar::with_draw_target(Some(0), ProgressDrawTarget::stderr());
+        if enabled {
+            bar.set_style(
+                ProgressStyle::with_template("[{elapsed_precise}] [{bar:30}] {pos}/{len} ({per_sec}, ETA {eta}) {msg}")
+                    .unwrap()
+                    .progress_chars("=> "),
+            );
+        } else {
+            bar.set_draw_target(ProgressDrawTarget::hidden());
+        }
+
+        Progress {
+            bar,
+            started: Instant::now(),
+            statuses: Arc::new(Mutex::new(BTreeMap::new())),
+            errors: Arc::new(AtomicUsize::new(0)),
+        }
+    }
+
+    pub fn add_total(&self, requests: usize) {
+        self.bar.inc_length(requests as u64);
+    }
+
+    // A wordlist request finished, with the status it got or None if it failed outright
+    pub fn record(&self, status: Option<u16>) {
+        match status {
+            Some(status) => *self.statuses.lock().unwrap().entry(status).or_insert(0) += 1,
+            None => {
+                self.errors.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        self.bar.set_message(self.stats());
+        self.bar.inc(1);
+    }
+
+    // Hits go to stdout like they always have, the bar is only cleared while they're printed so
+    // the two don't get mixed up
+    pub fn println(&self, line: &str) {
+        self.bar.suspend(|| println!("{}", line));
+    }
+
+    pub fn eprintln(&self, line: &str) {
+        self.bar.suspend(|| eprintln!("{}", line));
+    }
+
+    pub fn finish(&self) {
+        self.bar.finish_and_clear();
+    }
+
+    // e.g. "errors: 2 | 200: 4, 301: 1, 404: 1203"
+    fn stats(&self) -> String {
+        let statuses = self.statuses.lock().unwrap();
+        let histogram: Vec<String> = statuses
+            .iter()
+            .map(|(status, count)| format!("{}: {}", status, count))
+            .collect();
+
+        format!("errors: {} | {}", self.errors.load(Ordering::Relaxed), histogram.join(", "))
+    }
+
+    // One line to sum up the run once the bar has gone
+    pub fn print_summary(&self) {
+        // Counted from the stats, finishing the bar moves its position to the end
+        let done = self.statuses.lock().unwrap().values().sum::<usize>() + self.errors.load(Ordering::Relaxed);
+        if done == 0 {
+            return;
+        }
+
+        let elapsed = self.started.elapsed().as_secs_f64().max(0.001);
+        println!("[*] {} request(s), {:.1} requests/second, {}", done, done as f64 / elapsed, self.stats());
+    }
+}
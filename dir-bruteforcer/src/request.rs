@@ -0,0 +1,193 @@
+use reqwest::header::{HeaderName, HeaderValue};
+use reqwest::{Client, Method, RequestBuilder};
+
+// Default placeholder replaced by the current word wherever it appears in the request
+pub const FUZZ_KEYWORD: &str = "FUZZ";
+
+// Parse a header given on the command line as "Name: value". Headers are kept as plain strings
+// since the name or value may contain FUZZ, which has to survive until the word is filled in.
+pub fn parse_header(s: &str) -> Result<(String, String), String> {
+    let (name, value) = s
+        .split_once(':')
+        .ok_or_else(|| format!("invalid header '{}', expected 'Name: value'", s))?;
+    let (name, value) = (name.trim().to_string(), value.trim().to_string());
+
+    // Check it would be valid once a (plain) word has been filled in
+    HeaderName::from_bytes(name.replace(FUZZ_KEYWORD, "x").as_bytes())
+        .map_err(|_| format!("invalid header name '{}'", name))?;
+    HeaderValue::from_str(&value.replace(FUZZ_KEYWORD, "x"))
+        .map_err(|_| format!("invalid value for header '{}'", name))?;
+
+    Ok((name, value))
+}
+
+// Parse an HTTP method, allowing anything that's a valid token so custom verbs can be used too
+pub fn parse_method(s: &str) -> Result<Method, String> {
+    Method::from_bytes(s.to_uppercase().as_bytes()).map_err(|_| format!("invalid HTTP method '{}'", s))
+}
+
+// Put together the headers sent with every request. Cookies given separately are joined into a
+// single Cookie header, after any that were already set with -H.
+pub fn build_headers(
+    headers: &[(String, String)],
+    cookies: &[String],
+    user_agent: Option<&str>,
+) -> Vec<(String, String)> {
+    let mut built: Vec<(String, String)> = headers.to_vec();
+
+    if !cookies.is_empty() {
+        let mut cookie = cookies.join("; ");
+        if let Some(index) = built.iter().position(|(name, _)| name.eq_ignore_ascii_case("cookie")) {
+            let (_, existing) = built.remove(index);
+            cookie = format!("{}; {}", existing, cookie);
+        }
+        built.push(("Cookie".to_string(), cookie));
+    }
+
+    if let Some(user_agent) = user_agent {
+        built.retain(|(name, _)| !name.eq_ignore_ascii_case("user-agent"));
+        built.push(("User-Agent".to_string(), user_agent.to_string()));
+    }
+
+    built
+}
+
+// Everything about a request that can contain a keyword, ffuf style. When none of the keywords are
+// used anywhere, the first one is appended to the URL, which gives the usual directory
+// bruteforcing behaviour.
+#[derive(Clone, Debug)]
+pub struct RequestTemplate {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub method: Option<Method>,
+    pub keyword: String,
+}
+
+impl RequestTemplate {
+    pub fn new(
+        url: &str,
+        method: Option<Method>,
+        headers: Vec<(String, String)>,
+        body: Option<String>,
+        keywords: &[String],
+    ) -> Self {
+        let keyword = keywords.first().cloned().unwrap_or_else(|| FUZZ_KEYWORD.to_string());
+
+        let uses_keyword = keywords.iter().any(|keyword| {
+            url.contains(keyword.as_str())
+                || body.as_deref().is_some_and(|body| body.contains(keyword.as_str()))
+                || headers
+                    .iter()
+                    .any(|(name, value)| name.contains(keyword.as_str()) || value.contains(keyword.as_str()))
+        });
+
+        let url = if uses_keyword {
+            url.to_string()
+        } else if url.ends_with('/') {
+            format!("{}{}", url, keyword)
+        } else {
+            format!("{}/{}", url, keyword)
+        };
+
+        RequestTemplate { url, headers, body, method, keyword }
+    }
+
+    // Same request, but with the words going under a different directory
+    pub fn for_directory(&self, directory_url: &str) -> Self {
+        RequestTemplate {
+            url: format!("{}{}", directory_url, self.keyword),
+            ..self.clone()
+        }
+    }
+
+    // A bodyless HEAD version of the request, to check whether something exists cheaply
+    pub fn as_head(&self) -> Self {
+        RequestTemplate {
+            method: Some(Method::HEAD),
+            body: None,
+            ..self.clone()
+        }
+    }
+
+    // Same request without a header, e.g. to see what the target does without our Host override
+    pub fn without_header(&self, name: &str) -> Self {
+        RequestTemplate {
+            headers: self
+                .headers
+                .iter()
+                .filter(|(header, _)| !header.eq_ignore_ascii_case(name))
+                .cloned()
+                .collect(),
+            ..self.clone()
+        }
+    }
+
+    // Recursion only makes sense when the word is the last part of the path
+    pub fn is_path_mode(&self) -> bool {
+        self.url.ends_with(&self.keyword) && self.url.matches(&self.keyword).count() == 1
+    }
+
+    // The URL with the keyword left off, for showing which directory is being scanned
+    pub fn base_url(&self) -> &str {
+        self.url.strip_suffix(&self.keyword).unwrap_or(&self.url)
+    }
+
+    pub fn render(&self, inputs: &[(String, String)]) -> PreparedRequest {
+        PreparedRequest {
+            url: fill(&self.url, inputs),
+            headers: self
+                .headers
+                .iter()
+                .map(|(name, value)| (fill(name, inputs), fill(value, inputs)))
+                .collect(),
+            body: self.body.as_ref().map(|body| fill(body, inputs)),
+            method: self.method.clone(),
+        }
+    }
+}
+
+// Replace every keyword in `text` with its word. Longer keywords go first so one keyword that
+// contains another (FUZZ and FUZZ2) isn't clobbered.
+fn fill(text: &str, inputs: &[(String, String)]) -> String {
+    let mut inputs: Vec<&(String, String)> = inputs.iter().collect();
+    inputs.sort_by_key(|(keyword, _)| std::cmp::Reverse(keyword.len()));
+
+    inputs
+        .iter()
+        .fold(text.to_string(), |text, (keyword, word)| text.replace(keyword.as_str(), word))
+}
+
+// A template with the word filled in, ready to send
+#[derive(Clone, Debug)]
+pub struct PreparedRequest {
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub method: Option<Method>,
+}
+
+impl PreparedRequest {
+    // Like curl, sending a body turns the request into a POST unless a method was given
+    pub fn method(&self) -> Method {
+        match (&self.method, &self.body) {
+            (Some(method), _) => method.clone(),
+            (None, Some(_)) => Method::POST,
+            (None, None) => Method::GET,
+        }
+    }
+
+    // Invalid header names or values are reported by reqwest when the request is sent
+    pub fn build(&self, client: &Client) -> RequestBuilder {
+        let mut builder = client.request(self.method(), &self.url);
+        if let Some(body) = &self.body {
+            builder = builder.body(body.clone());
+        }
+
+        for (name, value) in &self.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        builder
+    }
+}
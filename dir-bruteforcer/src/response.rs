@@ -0,0 +1,192 @@
+use std::borrow::Cow;
+use std::time::{Duration, Instant};
+
+// Path fragments that usually mean a redirect is bouncing us to authentication
+const LOGIN_MARKERS: &[&str] = &["login", "logon", "signin", "sign-in", "sign_in", "auth", "sso", "saml", "oauth"];
+
+// Only this much of a body is kept, so a huge backup or dump file can't use up all the memory
+const MAX_BODY_SIZE: usize = 5 * 1024 * 1024;
+
+// The parts of a response we care about once the body has been read
+#[derive(Clone, Debug)]
+pub struct ScanResponse {
+    pub url: String,
+    pub word: String,
+    pub status: u16,
+    pub size: usize,
+    pub words: usize,
+    pub lines: usize,
+    pub redirect: Option<String>,
+    pub content_type: Option<String>,
+    pub duration: Duration,
+    pub flags: Vec<String>,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl ScanResponse {
+    // `started` is when the request was sent, so the duration covers reading the whole body
+    pub async fn read(url: &str, word: &str, mut res: reqwest::Response, started: Instant) -> Result<Self, reqwest::Error> {
+        let status = res.status().as_u16();
+        let headers = res
+            .headers()
+            .iter()
+            .map(|(name, value)| (name.to_string(), String::from_utf8_lossy(value.as_bytes()).to_string()))
+            .collect();
+        let content_type = res
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .map(|value| String::from_utf8_lossy(value.as_bytes()).to_string());
+
+        // Either the Location header of a redirect we didn't follow, or wherever reqwest ended up
+        // after following one. Relative locations are resolved against the requested URL.
+        let redirect = match res.headers().get(reqwest::header::LOCATION) {
+            Some(location) => {
+                let location = String::from_utf8_lossy(location.as_bytes()).to_string();
+                match res.url().join(&location) {
+                    Ok(resolved) => Some(resolved.to_string()),
+                    Err(_) => Some(location),
+                }
+            }
+            None if reqwest::Url::parse(url).is_ok_and(|requested| &requested != res.url()) => {
+                Some(res.url().to_string())
+            }
+            None => None,
+        };
+
+        // Size is taken from the body we actually received rather than the content-length header,
+        // which is missing for chunked responses. Bodies over the limit are cut short, their size
+        // comes from the header if there is one and the word and line counts only cover what was read.
+        let content_length = res.content_length();
+        let mut body = Vec::new();
+        let mut truncated = false;
+        while let Some(chunk) = res.chunk().await? {
+            let room = MAX_BODY_SIZE - body.len();
+            if chunk.len() > room {
+                body.extend_from_slice(&chunk[..room]);
+                truncated = true;
+                break;
+            }
+            body.extend_from_slice(&chunk);
+        }
+        let size = match content_length {
+            Some(length) if truncated => length as usize,
+            _ => body.len(),
+        };
+        let text = String::from_utf8_lossy(&body);
+        let (words, lines) = (text.split_whitespace().count(), text.lines().count());
+
+        Ok(ScanResponse {
+            url: url.to_string(),
+            word: word.to_string(),
+            status,
+            size,
+            words,
+            lines,
+            redirect,
+            content_type,
+            duration: started.elapsed(),
+            flags: if truncated { vec!["truncated".to_string()] } else { Vec::new() },
+            headers,
+            body,
+        }
+        .with_redirect_flags())
+    }
+
+    pub fn body_text(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(&self.body)
+    }
+
+    // The response headers as they'd appear on the wire, one "Name: value" per line
+    pub fn header_text(&self) -> String {
+        self.headers
+            .iter()
+            .map(|(name, value)| format!("{}: {}\n", name, value))
+            .collect()
+    }
+
+    // Note redirects that probably aren't the page itself: ones to a login form, and ones that
+    // leave the host we're scanning
+    fn with_redirect_flags(mut self) -> Self {
+        let (Some(redirect), Ok(requested)) = (&self.redirect, reqwest::Url::parse(&self.url)) else {
+            return self;
+        };
+        let Ok(target) = requested.join(redirect) else {
+            return self;
+        };
+
+        // Markers have to start a path segment and not run on into another word, so /login.php and
+        // /auth/ count but /author/ and /lessons/ don't
+        let path = target.path().to_lowercase();
+        let is_login = path.split('/').any(|segment| {
+            LOGIN_MARKERS.iter().any(|marker| {
+                segment
+                    .strip_prefix(marker)
+                    .is_some_and(|rest| !rest.starts_with(|c: char| c.is_ascii_alphabetic()))
+            })
+        });
+        if is_login {
+            self.flags.push("login".to_string());
+        }
+
+        // Only the host, an upgrade to https on the same host isn't leaving it
+        if target.host_str() != requested.host_str() {
+            self.flags.push("off-host".to_string());
+        }
+
+        self
+    }
+
+    // Returns the URL to recurse into if this response looks like a directory, either because it
+    // redirected to the same path with a trailing slash or because a path ending in a slash exists
+    pub fn directory_url(&self) -> Option<String> {
+        let with_slash = format!("{}/", self.url);
+        if self.redirect.as_deref() == Some(with_slash.as_str()) {
+            return Some(with_slash);
+        }
+
+        if self.url.ends_with('/') && matches!(self.status, 200..=299 | 401 | 403) {
+            return Some(self.url.clone());
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn redirect_flags(url: &str, redirect: &str) -> Vec<String> {
+        ScanResponse {
+            url: url.to_string(),
+            word: String::new(),
+            status: 302,
+            size: 0,
+            words: 0,
+            lines: 0,
+            redirect: Some(redirect.to_string()),
+            content_type: None,
+            duration: Duration::ZERO,
+            flags: Vec::new(),
+            headers: Vec::new(),
+            body: Vec::new(),
+        }
+        .with_redirect_flags()
+        .flags
+    }
+
+    #[test]
+    fn login_markers_start_a_path_segment() {
+        assert_eq!(redirect_flags("http://example.com/admin", "/login.php"), ["login"]);
+        assert_eq!(redirect_flags("http://example.com/admin", "/auth/"), ["login"]);
+        assert!(redirect_flags("http://example.com/posts", "/author/bob").is_empty());
+        assert!(redirect_flags("http://example.com/learn", "/lessons/").is_empty());
+    }
+
+    #[test]
+    fn off_host_ignores_scheme_and_port() {
+        assert!(redirect_flags("http://example.com/x", "https://example.com/x/").is_empty());
+        assert_eq!(redirect_flags("http://example.com/x", "https://other.example.org/"), ["off-host"]);
+    }
+}
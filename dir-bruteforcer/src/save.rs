@@ -0,0 +1,100 @@
+use crate::scan::{ResultSink, ScanResult};
+use reqwest::StatusCode;
+use std::collections::HashSet;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+// Long paths are cut down to this many characters, file systems usually stop at 255
+const MAX_NAME_LENGTH: usize = 150;
+
+// Writes the headers and body of every result to its own file, with index.txt listing which file
+// holds which URL
+#[derive(Debug)]
+pub struct ResponseSaver {
+    dir: PathBuf,
+    index: Mutex<BufWriter<File>>,
+    names: Mutex<HashSet<String>>,
+}
+
+impl ResponseSaver {
+    // When resuming, the index from last time is added to rather than started again, and the files
+    // already in the directory keep their names so nothing from before gets overwritten
+    pub fn create(dir: &str, resume: bool) -> io::Result<Self> {
+        let dir = PathBuf::from(dir);
+        fs::create_dir_all(&dir)?;
+
+        let index_path = dir.join("index.txt");
+        let mut names = HashSet::new();
+        let mut index = if resume && index_path.exists() {
+            for entry in fs::read_dir(&dir)? {
+                names.insert(entry?.file_name().to_string_lossy().into_owned());
+            }
+            BufWriter::new(OpenOptions::new().append(true).open(&index_path)?)
+        } else {
+            let mut index = BufWriter::new(File::create(&index_path)?);
+            writeln!(index, "# url\tword\tstatus\tfile")?;
+            index
+        };
+        index.flush()?;
+
+        Ok(ResponseSaver {
+            dir,
+            index: Mutex::new(index),
+            names: Mutex::new(names),
+        })
+    }
+
+    // Host and path squashed into something safe to use as a file name, e.g.
+    // http://example.com:8080/admin/login.php?x=1 -> example.com_8080_admin_login.php_x_1.txt
+    // Names that are already taken get a number on the end, which happens in vhost mode
+    fn file_name(&self, url: &str) -> String {
+        let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+        let mut name: String = without_scheme
+            .trim_end_matches('/')
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() || c == '.' || c == '-' { c } else { '_' })
+            .take(MAX_NAME_LENGTH)
+            .collect();
+        if name.is_empty() {
+            name = "index".to_string();
+        }
+
+        let mut names = self.names.lock().unwrap();
+        let mut candidate = format!("{}.txt", name);
+        let mut count = 1;
+        while names.contains(&candidate) {
+            count += 1;
+            candidate = format!("{}-{}.txt", name, count);
+        }
+        names.insert(candidate.clone());
+
+        candidate
+    }
+}
+
+impl ResultSink for ResponseSaver {
+    fn result(&self, result: &ScanResult) -> io::Result<()> {
+        let response = &result.response;
+        let name = self.file_name(&response.url);
+
+        // Laid out like a raw HTTP response so it can be read (and grepped) as one
+        let reason = StatusCode::from_u16(response.status)
+            .ok()
+            .and_then(|status| status.canonical_reason())
+            .unwrap_or("");
+        let mut file = BufWriter::new(File::create(self.dir.join(&name))?);
+        writeln!(file, "HTTP/1.1 {} {}", response.status, reason)?;
+        for (header, value) in &response.headers {
+            writeln!(file, "{}: {}", header, value)?;
+        }
+        writeln!(file)?;
+        file.write_all(&response.body)?;
+        file.flush()?;
+
+        let mut index = self.index.lock().unwrap();
+        writeln!(index, "{}\t{}\t{}\t{}", response.url, response.word, response.status, name)?;
+        index.flush()
+    }
+}
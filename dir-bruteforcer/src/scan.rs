@@ -0,0 +1,715 @@
+use crate::auth::Auth;
+use crate::calibration::{random_word, Calibration};
+use crate::client::{build_client, ClientOptions};
+use crate::errors::{ErrorStats, RequestError};
+use crate::filters::Filters;
+use crate::output::ResultRecord;
+use crate::request::{PreparedRequest, RequestTemplate};
+use crate::response::ScanResponse;
+use crate::state::{scanned_list, ScanState, StateTracker};
+use crate::throttle::RateLimiter;
+use crate::timing::RunningMedian;
+use crate::wordlist::{describe_inputs, Inputs, Wordlists};
+use futures::channel::mpsc::{self, UnboundedSender};
+use futures::stream::{Stream, StreamExt};
+use reqwest::{Client, Method, Response, StatusCode};
+use std::collections::{HashSet, VecDeque};
+use std::error::Error;
+use std::io;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+
+// Retry delays stop doubling after this many attempts
+const MAX_BACKOFF_DOUBLINGS: u32 = 16;
+
+// How a scan runs. Use ScanConfig::builder, which fills in the same defaults as the command line.
+#[derive(Clone, Debug)]
+pub struct ScanConfig {
+    pub wordlists: Wordlists,
+    pub client: Client,
+    pub replay_client: Option<Client>,
+    pub auth: Option<Arc<Auth>>,
+    pub threads: usize,
+    pub rate: Option<u32>,
+    pub delay: Duration,
+    pub retries: u32,
+    pub retry_backoff: Duration,
+    pub filters: Filters,
+    pub calibrate: bool,
+    pub head_first: bool,
+    pub slow_factor: f64,
+    pub vhost: Option<String>,
+    pub depth: usize,
+    pub deny_dirs: Vec<String>,
+    pub debug: bool,
+}
+
+impl ScanConfig {
+    pub fn builder(wordlists: Wordlists) -> ScanConfigBuilder {
+        ScanConfigBuilder {
+            wordlists,
+            client_options: ClientOptions::default(),
+            replay_proxy: None,
+            auth: None,
+            threads: 10,
+            rate: None,
+            delay: Duration::ZERO,
+            retries: 0,
+            retry_backoff: Duration::from_millis(500),
+            filters: Filters::default(),
+            calibrate: true,
+            head_first: false,
+            slow_factor: 5.0,
+            vhost: None,
+            depth: 2,
+            deny_dirs: Vec::new(),
+            debug: false,
+        }
+    }
+
+    // The request template for a target. In vhost mode the first keyword goes in the Host header
+    // and the URL is left as it is, and a body is sent as `content_type` unless the headers
+    // already have a Content-Type.
+    pub fn template(
+        &self,
+        url: &str,
+        method: Option<Method>,
+        headers: &[(String, String)],
+        body: Option<String>,
+        content_type: &str,
+    ) -> RequestTemplate {
+        let keywords = self.wordlists.keywords();
+        let mut headers = headers.to_vec();
+
+        if let Some(domain) = &self.vhost {
+            headers.retain(|(name, _)| !name.eq_ignore_ascii_case("host"));
+            headers.push(("Host".to_string(), format!("{}.{}", keywords[0], domain.trim_start_matches('.'))));
+        }
+
+        let has_content_type = headers.iter().any(|(name, _)| name.eq_ignore_ascii_case("content-type"));
+        if body.is_some() && !has_content_type {
+            headers.push(("Content-Type".to_string(), content_type.to_string()));
+        }
+
+        RequestTemplate::new(url, method, headers, body, &keywords)
+    }
+
+    fn is_denied(&self, directory_url: &str) -> bool {
+        let path = directory_url.trim_end_matches('/').to_lowercase();
+        self.deny_dirs.iter().any(|denied| {
+            let denied = denied.trim_matches('/').to_lowercase();
+            !denied.is_empty() && path.ends_with(&format!("/{}", denied))
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct ScanConfigBuilder {
+    wordlists: Wordlists,
+    client_options: ClientOptions,
+    replay_proxy: Option<String>,
+    auth: Option<Arc<Auth>>,
+    threads: usize,
+    rate: Option<u32>,
+    delay: Duration,
+    retries: u32,
+    retry_backoff: Duration,
+    filters: Filters,
+    calibrate: bool,
+    head_first: bool,
+    slow_factor: f64,
+    vhost: Option<String>,
+    depth: usize,
+    deny_dirs: Vec<String>,
+    debug: bool,
+}
+
+impl ScanConfigBuilder {
+    pub fn client_options(mut self, client_options: ClientOptions) -> Self {
+        self.client_options = client_options;
+        self
+    }
+
+    // Matched requests are sent again through this proxy, e.g. to get them into Burp's history
+    pub fn replay_proxy(mut self, replay_proxy: Option<String>) -> Self {
+        self.replay_proxy = replay_proxy;
+        self
+    }
+
+    pub fn auth(mut self, auth: Option<Auth>) -> Self {
+        self.auth = auth.map(Arc::new);
+        self
+    }
+
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    // 0 means no limit, the same as leaving it out
+    pub fn rate(mut self, rate: Option<u32>) -> Self {
+        self.rate = rate.filter(|&rate| rate > 0);
+        self
+    }
+
+    pub fn delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    pub fn retries(mut self, retries: u32, backoff: Duration) -> Self {
+        self.retries = retries;
+        self.retry_backoff = backoff;
+        self
+    }
+
+    pub fn filters(mut self, filters: Filters) -> Self {
+        self.filters = filters;
+        self
+    }
+
+    pub fn calibrate(mut self, calibrate: bool) -> Self {
+        self.calibrate = calibrate;
+        self
+    }
+
+    pub fn head_first(mut self, head_first: bool) -> Self {
+        self.head_first = head_first;
+        self
+    }
+
+    // Responses taking more than this many times the median are flagged as slow, 0 turns it off
+    pub fn slow_factor(mut self, slow_factor: f64) -> Self {
+        self.slow_factor = slow_factor;
+        self
+    }
+
+    // The domain being fuzzed in vhost mode, ScanConfig::template puts it in the Host header
+    pub fn vhost(mut self, vhost: Option<String>) -> Self {
+        self.vhost = vhost;
+        self
+    }
+
+    pub fn depth(mut self, depth: usize) -> Self {
+        self.depth = depth;
+        self
+    }
+
+    pub fn deny_dirs(mut self, deny_dirs: Vec<String>) -> Self {
+        self.deny_dirs = deny_dirs;
+        self
+    }
+
+    // Failed requests are passed to the sinks as errors instead of only being counted
+    pub fn debug(mut self, debug: bool) -> Self {
+        self.debug = debug;
+        self
+    }
+
+    pub fn build(self) -> Result<ScanConfig, Box<dyn Error>> {
+        let client = build_client(&self.client_options)
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+        let replay_client = match &self.replay_proxy {
+            Some(replay_proxy) => {
+                let replay_options = ClientOptions {
+                    proxy: Some(replay_proxy.clone()),
+                    ..self.client_options.clone()
+                };
+                let client = build_client(&replay_options)
+                    .map_err(|e| format!("Failed to build replay proxy client: {}", e))?;
+                Some(client)
+            }
+            None => None,
+        };
+
+        Ok(ScanConfig {
+            wordlists: self.wordlists,
+            client,
+            replay_client,
+            auth: self.auth,
+            threads: self.threads,
+            rate: self.rate,
+            delay: self.delay,
+            retries: self.retries,
+            retry_backoff: self.retry_backoff,
+            filters: self.filters,
+            calibrate: self.calibrate,
+            head_first: self.head_first,
+            slow_factor: self.slow_factor,
+            vhost: self.vhost,
+            depth: self.depth,
+            deny_dirs: self.deny_dirs,
+            debug: self.debug,
+        })
+    }
+}
+
+// A response that got through the filters and calibration
+#[derive(Clone, Debug)]
+pub struct ScanResult {
+    // The target as it was given, without the keyword
+    pub target: String,
+    pub response: ScanResponse,
+    // What --match-regex found, with a little context
+    pub matched: Option<String>,
+}
+
+// Somewhere for results to go. Only `result` has to be implemented, the rest are for anything that
+// wants to show how the scan is getting on.
+pub trait ResultSink: Send + Sync {
+    fn result(&self, result: &ScanResult) -> io::Result<()>;
+
+    // Notes like a directory being scanned or wildcard responses being detected
+    fn info(&self, _target: &str, _message: &str) {}
+
+    // Failed requests (in debug mode) and sinks that couldn't take a result
+    fn error(&self, _message: &str) {}
+
+    // More wordlist requests are on the way, as each directory starts
+    fn requests_queued(&self, _count: usize) {}
+
+    // A wordlist request finished, with its status or None if it failed
+    fn request_done(&self, _status: Option<u16>) {}
+
+    fn target_finished(&self, _target: &str) {}
+}
+
+// Turns the results into a stream for Scanner::into_stream
+struct ChannelSink {
+    sender: UnboundedSender<ScanResult>,
+}
+
+impl ResultSink for ChannelSink {
+    fn result(&self, result: &ScanResult) -> io::Result<()> {
+        // The receiver going away just means nobody wants the rest of the results
+        let _ = self.sender.unbounded_send(result.clone());
+        Ok(())
+    }
+}
+
+// Runs scans with a config, sharing the concurrency and rate limits between every target
+pub struct Scanner {
+    config: ScanConfig,
+    concurrency: Semaphore,
+    rate_limiter: Option<RateLimiter>,
+    errors: ErrorStats,
+    state: StateTracker,
+    sinks: Vec<Arc<dyn ResultSink>>,
+}
+
+impl Scanner {
+    pub fn new(config: ScanConfig) -> Self {
+        let state = ScanState {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            wordlist_size: config.wordlists.len(),
+            ..ScanState::default()
+        };
+
+        Scanner {
+            concurrency: Semaphore::new(config.threads.max(1)),
+            rate_limiter: config.rate.map(RateLimiter::new),
+            errors: ErrorStats::default(),
+            state: StateTracker::new(state),
+            sinks: Vec::new(),
+            config,
+        }
+    }
+
+    // Carry on from a saved state. The saved positions are indexes into the wordlists, so they
+    // only line up if the wordlists (and extensions) are the same as last time.
+    pub fn with_state(mut self, state: ScanState) -> Result<Self, Box<dyn Error>> {
+        if state.wordlist_size != self.config.wordlists.len() {
+            return Err(format!(
+                "saved with {} requests per directory but the wordlists now give {}",
+                state.wordlist_size,
+                self.config.wordlists.len()
+            )
+            .into());
+        }
+
+        self.state = StateTracker::new(state);
+        Ok(self)
+    }
+
+    pub fn with_sink(mut self, sink: Arc<dyn ResultSink>) -> Self {
+        self.sinks.push(sink);
+        self
+    }
+
+    pub fn config(&self) -> &ScanConfig {
+        &self.config
+    }
+
+    pub fn state(&self) -> &StateTracker {
+        &self.state
+    }
+
+    pub fn errors(&self) -> &ErrorStats {
+        &self.errors
+    }
+
+    // Scan every target side by side. Results go to the sinks as they're found.
+    pub async fn run(&self, templates: &[RequestTemplate]) {
+        // Indexing rather than iterating over references keeps the future Send for into_stream
+        futures::stream::iter((0..templates.len()).map(|index| self.scan_target(&templates[index])))
+            .buffer_unordered(self.config.threads.max(1))
+            .for_each(|_| async {})
+            .await;
+    }
+
+    async fn scan_target(&self, template: &RequestTemplate) {
+        self.scan_recursively(template).await;
+        for sink in &self.sinks {
+            sink.target_finished(template.base_url());
+        }
+    }
+
+    // Run the scan in the background and get the results as a stream, which ends with the scan
+    pub fn into_stream(self, templates: Vec<RequestTemplate>) -> impl Stream<Item = ScanResult> {
+        let (sender, receiver) = mpsc::unbounded();
+        let scanner = self.with_sink(Arc::new(ChannelSink { sender }));
+
+        tokio::spawn(async move {
+            scanner.run(&templates).await;
+        });
+
+        receiver
+    }
+
+    fn info(&self, target: &str, message: &str) {
+        for sink in &self.sinks {
+            sink.info(target, message);
+        }
+    }
+
+    fn error(&self, message: &str) {
+        for sink in &self.sinks {
+            sink.error(message);
+        }
+    }
+
+    // Requests repeated after resuming can find the same thing again, it's already been handed out
+    fn report(&self, result: ScanResult) {
+        if !self.state.add_result(ResultRecord::from(&result.response)) {
+            return;
+        }
+
+        for sink in &self.sinks {
+            if let Err(e) = sink.result(&result) {
+                self.error(&format!("[-] Failed to write result for {}: {}", result.response.url, e));
+            }
+        }
+    }
+
+    // Request a few paths that can't exist so catch-all and custom 404 responses can be recognised
+    async fn calibrate(&self, template: &RequestTemplate) -> Calibration {
+        let mut calibration = Calibration::default();
+
+        let keywords = self.config.wordlists.keywords();
+
+        // The first keyword gets the probe word, any others just need something random that won't exist
+        for word in Calibration::probe_words() {
+            let inputs: Inputs = keywords
+                .iter()
+                .enumerate()
+                .map(|(i, keyword)| (keyword.clone(), if i == 0 { word.clone() } else { random_word(12) }))
+                .collect();
+
+            if let Ok(response) = self.make_request(template, &inputs).await {
+                calibration.add(&response, &word);
+            }
+        }
+
+        calibration
+    }
+
+    // Scan the target, then keep scanning any directories found until the depth limit is reached.
+    // Progress is kept in the state tracker as it goes, and picked back up from there when resuming.
+    async fn scan_recursively(&self, template: &RequestTemplate) {
+        let target = template.url.as_str();
+
+        // The queue holds directory URLs, with the target itself as the empty string at depth 0
+        let (mut queue, mut scanned, mut position) = match self.state.resume_point(target) {
+            Some(saved) if saved.finished => return,
+            Some(saved) if !saved.queue.is_empty() => (
+                VecDeque::from(saved.queue),
+                saved.scanned.into_iter().collect::<HashSet<String>>(),
+                saved.position,
+            ),
+            _ => (VecDeque::from([(String::new(), 0)]), HashSet::new(), 0),
+        };
+
+        // Response times are compared per target, different servers can be very different
+        let latency = RunningMedian::default();
+
+        // Each directory stays at the front of the queue until it's done, so an interrupted scan
+        // saves it as the one to carry on with
+        while let Some((directory, depth)) = queue.front().cloned() {
+            if scanned.contains(&directory) {
+                queue.pop_front();
+                continue;
+            }
+
+            self.state.begin_directory(target, queue.iter().cloned().collect(), scanned_list(&scanned), position);
+
+            let directory_template = if depth == 0 {
+                template.clone()
+            } else {
+                template.for_directory(&directory)
+            };
+
+            for sink in &self.sinks {
+                sink.requests_queued(self.config.wordlists.len().saturating_sub(position));
+            }
+
+            if depth > 0 {
+                self.info(template.base_url(), &format!("[*] Scanning directory {}", directory_template.base_url()));
+            }
+
+            for sub_directory in self.process_urls_concurrently(template, &directory_template, &latency, depth, position).await {
+                queue.push_back((sub_directory, depth + 1));
+            }
+
+            queue.pop_front();
+            scanned.insert(directory);
+            position = 0;
+        }
+
+        self.state.finish_target(target);
+    }
+
+    // Send the request template for every combination of words, returning any directories found
+    async fn process_urls_concurrently(&self, root: &RequestTemplate, template: &RequestTemplate, latency: &RunningMedian, depth: usize, position: usize) -> Vec<String> {
+        let filters = &self.config.filters;
+        let target = root.url.as_str();
+        let label = root.base_url();
+
+        // Directories only mean something when the words are being appended to the path
+        let recurse = template.is_path_mode() && depth < self.config.depth;
+
+        let mut calibration = if self.config.calibrate {
+            self.calibrate(template).await
+        } else {
+            Calibration::default()
+        };
+
+        // In vhost mode anything that looks like the default site is just the server ignoring our
+        // Host header, so that's used as a baseline on top of the usual calibration
+        if self.config.vhost.is_some() {
+            let baseline = template.without_header("host");
+            if let Ok(response) = self.make_request(&baseline, &[]).await {
+                self.info(label, &format!(
+                    "[*] Default vhost baseline: status {}, size {}, words {}",
+                    response.status, response.size, response.words
+                ));
+                calibration.add(&response, "");
+            }
+        }
+
+        // Only worth mentioning if the wildcard responses would otherwise have been reported
+        let wildcards = calibration
+            .fingerprints
+            .iter()
+            .filter(|fingerprint| filters.match_codes.contains(fingerprint.status as u64))
+            .count();
+        if wildcards > 0 {
+            self.info(label, &format!("[*] {} looks like it has wildcard responses, suppressing {} pattern(s)", template.base_url(), wildcards));
+        }
+
+        let calibration = &calibration;
+
+        // Create a stream of tasks with a concurrency limit for our threads variable
+        // Reference: https://gendignoux.com/blog/2021/04/01/rust-async-streams-futures-part1.html
+        let tasks = futures::stream::iter(
+            (position..self.config.wordlists.len()).map(|index| {
+                    let inputs = self.config.wordlists.get(index);
+
+                    // Spawn async task for each URL
+                    async move {
+                        let result = self.fetch(template, &inputs).await;
+                        for sink in &self.sinks {
+                            sink.request_done(result.as_ref().ok().map(|response| response.status));
+                        }
+
+                        // A response that's much slower than usual is worth a look even when it
+                        // otherwise looks just like the wildcard response
+                        let result = result.map(|mut response| {
+                            if latency.is_slow(response.duration, self.config.slow_factor) {
+                                response.flags.push("slow".to_string());
+                            }
+                            latency.add(response.duration);
+                            response
+                        });
+
+                        let directory = match result {
+                            Ok(response)
+                                if filters.is_match(&response)
+                                    && (response.flags.iter().any(|flag| flag == "slow")
+                                        || !calibration.is_wildcard(&response, &inputs[0].1)) =>
+                            {
+                                let directory = response
+                                    .directory_url()
+                                    .filter(|directory| recurse && !self.config.is_denied(directory));
+                                let word = response.word.clone();
+
+                                self.report(ScanResult {
+                                    target: label.to_string(),
+                                    matched: filters.snippet(&response),
+                                    response,
+                                });
+                                self.replay_request(&template.render(&inputs), &word).await;
+
+                                directory
+                            }
+                            _ => None,
+                        };
+
+                        if let Some(directory) = &directory {
+                            self.state.found_directory(target, directory, depth + 1);
+                        }
+                        self.state.complete(target, index);
+
+                        directory
+                    }
+                })
+        ).buffer_unordered(self.config.threads); // Limit concurrency to `concurrency_limit`, the shared semaphore keeps the total across targets in check
+
+        // Execute all tasks and keep the directories that were found
+        tasks
+            .filter_map(|directory| async { directory })
+            .collect::<Vec<String>>()
+            .await
+    }
+
+    // Make the request for one set of words. With head_first a HEAD request goes out first, and the
+    // full request is only sent if its status code could be a hit.
+    async fn fetch(&self, template: &RequestTemplate, inputs: &[(String, String)]) -> Result<ScanResponse, RequestError> {
+        if self.config.head_first {
+            let head = self.make_request(&template.as_head(), inputs).await?;
+            if head_rules_out(&self.config.filters, head.status) {
+                return Ok(head);
+            }
+        }
+
+        self.make_request(template, inputs).await
+    }
+
+    // Send a request and read the response, retrying with an increasing delay when the failure looks
+    // temporary. Failures are counted rather than stopping the scan.
+    async fn make_request(&self, template: &RequestTemplate, inputs: &[(String, String)]) -> Result<ScanResponse, RequestError> {
+        let request = template.render(inputs);
+        let word = describe_inputs(inputs);
+        let mut attempt = 0;
+
+        loop {
+            let result = {
+                // Every target shares the same pool of permits, so the thread count is a limit for the whole run
+                let _permit = self.concurrency.acquire().await.expect("semaphore is never closed");
+                self.throttle().await;
+                let result = send_request(&self.config.client, self.config.auth.as_deref(), &request, &word).await;
+
+                // Holding on to the permit while sleeping is what makes the delay apply across every task
+                if !self.config.delay.is_zero() {
+                    tokio::time::sleep(self.config.delay).await;
+                }
+
+                result
+            };
+
+            let error = match result {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+
+            if attempt < self.config.retries && error.kind.is_retryable() {
+                self.errors.record_retry();
+                // Doubles every attempt, the exponent is capped so a big --retries can't overflow it
+                let factor = 2u32.checked_pow(attempt.min(MAX_BACKOFF_DOUBLINGS)).unwrap_or(u32::MAX);
+                tokio::time::sleep(self.config.retry_backoff.saturating_mul(factor)).await;
+                attempt += 1;
+                continue;
+            }
+
+            if self.config.debug {
+                self.error(&format!("[-] {}", error));
+            }
+            self.errors.record(&error);
+            return Err(error);
+        }
+    }
+
+    // Wait for the rate limiter, if there is one, before a request goes out
+    async fn throttle(&self) {
+        if let Some(rate_limiter) = &self.rate_limiter {
+            rate_limiter.acquire().await;
+        }
+    }
+
+    // Send a matched request again through the replay proxy. The response doesn't matter, it only needs
+    // to end up in the proxy's history.
+    async fn replay_request(&self, request: &PreparedRequest, word: &str) {
+        if let Some(replay_client) = &self.config.replay_client {
+            self.throttle().await;
+            if let Err(e) = send_request(replay_client, self.config.auth.as_deref(), request, word).await {
+                if self.config.debug {
+                    self.error(&format!("[-] Replay failed: {}", e));
+                }
+            }
+        }
+    }
+}
+
+// Whether a HEAD response is enough to skip the full request. Servers that don't do HEAD answer
+// every path with 405 or 501, which says nothing about whether the path exists.
+fn head_rules_out(filters: &Filters, status: u16) -> bool {
+    !matches!(status, 405 | 501) && !filters.status_matches(status)
+}
+
+// With Digest auth the first request to a host gets a 401 with the challenge in it, so that one
+// is sent again with the answer. The duration covers both.
+async fn send_request(client: &Client, auth: Option<&Auth>, request: &PreparedRequest, word: &str) -> Result<ScanResponse, RequestError> {
+    let url = &request.url;
+    let started = Instant::now();
+
+    // HTTP requests reference: https://rust-lang-nursery.github.io/rust-cookbook/web/clients/requests.html
+    let answered = auth.is_some_and(|auth| auth.has_challenge(url));
+    let mut res = send(client, auth, request).await?;
+
+    if let Some(auth) = auth {
+        if res.status() == StatusCode::UNAUTHORIZED && auth.handle_challenge(url, res.headers(), answered) {
+            res = send(client, Some(auth), request).await?;
+        }
+    }
+
+    ScanResponse::read(url, word, res, started)
+        .await
+        .map_err(|e| RequestError::new(url, e))
+}
+
+async fn send(client: &Client, auth: Option<&Auth>, request: &PreparedRequest) -> Result<Response, RequestError> {
+    let mut builder = request.build(client);
+    if let Some(auth) = auth {
+        builder = auth.apply(builder, &request.method(), &request.url);
+    }
+
+    builder.send().await.map_err(|e| RequestError::new(&request.url, e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn head_not_supported_falls_back_to_full_request() {
+        let filters = Filters {
+            filter_codes: "405".parse().unwrap(),
+            ..Filters::default()
+        };
+        assert!(!head_rules_out(&filters, 501));
+        assert!(!head_rules_out(&filters, 405));
+        assert!(!head_rules_out(&filters, 200));
+        assert!(head_rules_out(&filters, 404));
+    }
+}
@@ -0,0 +1,195 @@
+use crate::output::ResultRecord;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+// Where a single target had got to. The directory at the front of the queue is the one that was
+// being scanned, and `position` is how far into the wordlist every request had finished.
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct TargetState {
+    pub target: String,
+    pub finished: bool,
+    pub queue: Vec<(String, usize)>,
+    pub scanned: Vec<String>,
+    pub position: usize,
+
+    // Requests that finished out of order, ahead of `position`
+    #[serde(skip)]
+    completed: BTreeSet<usize>,
+}
+
+// Everything needed to pick a scan back up after it was interrupted
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
+pub struct ScanState {
+    pub version: String,
+    pub wordlist_size: usize,
+    pub targets: Vec<TargetState>,
+    pub results: Vec<ResultRecord>,
+}
+
+impl ScanState {
+    pub fn load<P: AsRef<Path>>(path: P) -> io::Result<Self> {
+        let data = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&data)?)
+    }
+}
+
+// Keeps the scan state up to date as requests finish, shared between all the request tasks
+#[derive(Debug, Default)]
+pub struct StateTracker {
+    state: Mutex<ScanState>,
+}
+
+impl StateTracker {
+    pub fn new(state: ScanState) -> Self {
+        StateTracker {
+            state: Mutex::new(state),
+        }
+    }
+
+    // The saved progress for a target, if there is any
+    pub fn resume_point(&self, target: &str) -> Option<TargetState> {
+        let state = self.state.lock().unwrap();
+        state.targets.iter().find(|t| t.target == target).cloned()
+    }
+
+    // Record the queue as a new directory starts being scanned from `position`
+    pub fn begin_directory(&self, target: &str, queue: Vec<(String, usize)>, scanned: Vec<String>, position: usize) {
+        self.update(target, |t| {
+            t.queue = queue;
+            t.scanned = scanned;
+            t.position = position;
+            t.completed.clear();
+        });
+    }
+
+    // A request finished, move the position forward past everything that's done in order
+    pub fn complete(&self, target: &str, index: usize) {
+        self.update(target, |t| {
+            t.completed.insert(index);
+            while t.completed.remove(&t.position) {
+                t.position += 1;
+            }
+        });
+    }
+
+    // Directories are added to the saved queue straight away so they aren't lost if the scan
+    // is interrupted before the current directory finishes
+    pub fn found_directory(&self, target: &str, directory: &str, depth: usize) {
+        self.update(target, |t| {
+            if !t.queue.iter().any(|(queued, _)| queued == directory) {
+                t.queue.push((directory.to_string(), depth));
+            }
+        });
+    }
+
+    pub fn finish_target(&self, target: &str) {
+        self.update(target, |t| {
+            t.finished = true;
+            t.queue.clear();
+            t.position = 0;
+        });
+    }
+
+    // Returns false if the result was already recorded, which happens for requests that get
+    // repeated after resuming
+    pub fn add_result(&self, record: ResultRecord) -> bool {
+        let mut state = self.state.lock().unwrap();
+        if state.results.iter().any(|r| r.url == record.url && r.word == record.word) {
+            return false;
+        }
+        state.results.push(record);
+        true
+    }
+
+    pub fn results(&self) -> Vec<ResultRecord> {
+        self.state.lock().unwrap().results.clone()
+    }
+
+    // Written to a temporary file first so an interruption mid-save can't corrupt the old state
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let data = {
+            let state = self.state.lock().unwrap();
+            serde_json::to_string_pretty(&*state)?
+        };
+
+        let path = path.as_ref();
+        let temp = path.with_extension("tmp");
+        fs::write(&temp, data)?;
+        fs::rename(&temp, path)
+    }
+
+    fn update<F: FnOnce(&mut TargetState)>(&self, target: &str, f: F) {
+        let mut state = self.state.lock().unwrap();
+        let index = match state.targets.iter().position(|t| t.target == target) {
+            Some(index) => index,
+            None => {
+                state.targets.push(TargetState {
+                    target: target.to_string(),
+                    ..TargetState::default()
+                });
+                state.targets.len() - 1
+            }
+        };
+        f(&mut state.targets[index]);
+    }
+}
+
+// The scanned set is kept as a Vec in the state file so it stays readable
+pub fn scanned_list(scanned: &HashSet<String>) -> Vec<String> {
+    let mut list: Vec<String> = scanned.iter().cloned().collect();
+    list.sort();
+    list
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TARGET: &str = "http://example.com/FUZZ";
+
+    fn position(tracker: &StateTracker) -> usize {
+        tracker.resume_point(TARGET).unwrap().position
+    }
+
+    #[test]
+    fn position_only_moves_past_requests_finished_in_order() {
+        let tracker = StateTracker::default();
+        tracker.begin_directory(TARGET, Vec::new(), Vec::new(), 0);
+
+        tracker.complete(TARGET, 2);
+        assert_eq!(position(&tracker), 0);
+        tracker.complete(TARGET, 0);
+        assert_eq!(position(&tracker), 1);
+        tracker.complete(TARGET, 1);
+        assert_eq!(position(&tracker), 3);
+    }
+
+    #[test]
+    fn new_directory_forgets_out_of_order_completions() {
+        let tracker = StateTracker::default();
+        tracker.begin_directory(TARGET, Vec::new(), Vec::new(), 0);
+        tracker.complete(TARGET, 1);
+
+        tracker.begin_directory(TARGET, Vec::new(), Vec::new(), 0);
+        tracker.complete(TARGET, 0);
+        assert_eq!(position(&tracker), 1);
+    }
+
+    #[test]
+    fn found_directory_is_only_queued_once() {
+        let tracker = StateTracker::default();
+        tracker.found_directory(TARGET, "http://example.com/admin/", 1);
+        tracker.found_directory(TARGET, "http://example.com/admin/", 1);
+        tracker.found_directory(TARGET, "http://example.com/images/", 1);
+
+        let queue = tracker.resume_point(TARGET).unwrap().queue;
+        assert_eq!(
+            queue,
+            [("http://example.com/admin/".to_string(), 1), ("http://example.com/images/".to_string(), 1)]
+        );
+    }
+}
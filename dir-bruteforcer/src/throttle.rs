@@ -0,0 +1,33 @@
+use std::time::Duration;
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+// Token bucket shared by every request task. The bucket only holds a single token so requests
+// are spread evenly instead of bursting, which keeps us under the cap for any one second window.
+#[derive(Debug)]
+pub struct RateLimiter {
+    interval: Duration,
+    next_token: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    pub fn new(requests_per_second: u32) -> Self {
+        RateLimiter {
+            interval: Duration::from_secs(1) / requests_per_second.max(1),
+            next_token: Mutex::new(Instant::now()),
+        }
+    }
+
+    // Wait until a token is available and take it. The lock is held while sleeping so waiting
+    // tasks are let through one at a time, in the order they asked.
+    pub async fn acquire(&self) {
+        let mut next_token = self.next_token.lock().await;
+
+        let now = Instant::now();
+        if *next_token > now {
+            tokio::time::sleep_until(*next_token).await;
+        }
+
+        *next_token = (*next_token).max(now) + self.interval;
+    }
+}
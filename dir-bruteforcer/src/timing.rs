@@ -0,0 +1,103 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::sync::Mutex;
+use std::time::Duration;
+
+// Not enough responses to know what normal looks like before this
+const MIN_SAMPLES: usize = 20;
+
+// Responses that are only a few milliseconds slower than a very fast median are just noise
+const MIN_SLOWDOWN_MS: u64 = 100;
+
+// Median response time for a target, kept as two heaps so it's cheap to update on every request.
+// The lower half is a max heap and the upper half a min heap, with the lower allowed one extra.
+// Reference: https://www.geeksforgeeks.org/median-of-stream-of-integers-running-integers/
+#[derive(Debug, Default)]
+pub struct RunningMedian {
+    halves: Mutex<(BinaryHeap<u64>, BinaryHeap<Reverse<u64>>)>,
+}
+
+impl RunningMedian {
+    pub fn add(&self, duration: Duration) {
+        let millis = duration.as_millis() as u64;
+        let mut halves = self.halves.lock().unwrap();
+        let (lower, upper) = &mut *halves;
+
+        match lower.peek() {
+            Some(&top) if millis > top => upper.push(Reverse(millis)),
+            _ => lower.push(millis),
+        }
+
+        if lower.len() > upper.len() + 1 {
+            upper.extend(lower.pop().map(Reverse));
+        } else if upper.len() > lower.len() {
+            lower.extend(upper.pop().map(|Reverse(millis)| millis));
+        }
+    }
+
+    // None until there are enough samples to go on
+    pub fn median(&self) -> Option<u64> {
+        let halves = self.halves.lock().unwrap();
+        let (lower, upper) = &*halves;
+
+        if lower.len() + upper.len() < MIN_SAMPLES {
+            return None;
+        }
+
+        match (lower.peek(), upper.peek()) {
+            (Some(&low), Some(&Reverse(high))) if lower.len() == upper.len() => Some((low + high) / 2),
+            (Some(&low), _) => Some(low),
+            _ => None,
+        }
+    }
+
+    // Whether a response took more than `factor` times the median so far
+    pub fn is_slow(&self, duration: Duration, factor: f64) -> bool {
+        if factor <= 0.0 {
+            return false;
+        }
+
+        let Some(median) = self.median() else {
+            return false;
+        };
+
+        let millis = duration.as_millis() as u64;
+        millis as f64 > median as f64 * factor && millis >= median + MIN_SLOWDOWN_MS
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn median_of(samples: impl IntoIterator<Item = u64>) -> RunningMedian {
+        let median = RunningMedian::default();
+        for millis in samples {
+            median.add(Duration::from_millis(millis));
+        }
+        median
+    }
+
+    #[test]
+    fn no_median_until_enough_samples() {
+        assert_eq!(median_of(1..MIN_SAMPLES as u64).median(), None);
+    }
+
+    #[test]
+    fn median_of_odd_and_even_counts() {
+        // Added in reverse so both heaps have to be rebalanced
+        let odd = median_of((1..=21).rev().map(|millis| millis * 10));
+        assert_eq!(odd.median(), Some(110));
+
+        let even = median_of((1..=20).rev().map(|millis| millis * 10));
+        assert_eq!(even.median(), Some(105));
+    }
+
+    #[test]
+    fn slow_needs_factor_and_minimum_slowdown() {
+        let median = median_of(std::iter::repeat_n(10, MIN_SAMPLES));
+        assert!(!median.is_slow(Duration::from_millis(100), 5.0));
+        assert!(median.is_slow(Duration::from_millis(200), 5.0));
+        assert!(!median.is_slow(Duration::from_millis(200), 0.0));
+    }
+}
@@ -0,0 +1,223 @@
+use crate::request::FUZZ_KEYWORD;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, BufRead};
+use std::path::Path;
+use std::str::FromStr;
+
+// The words for one request, as (keyword, word) pairs in the order the wordlists were given
+pub type Inputs = Vec<(String, String)>;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AttackMode {
+    /// Try every combination of words from all the wordlists
+    Clusterbomb,
+    /// Take one word from each wordlist at a time, stopping at the end of the shortest list
+    Pitchfork,
+}
+
+// A wordlist given on the command line as "path" or "path:KEYWORD"
+#[derive(Clone, Debug)]
+pub struct WordlistSpec {
+    pub path: String,
+    pub keyword: String,
+}
+
+impl FromStr for WordlistSpec {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // Only treat the part after the last colon as a keyword if it looks like one, so paths
+        // like C:\lists\words.txt still work
+        if let Some((path, keyword)) = s.rsplit_once(':') {
+            let is_keyword = !keyword.is_empty()
+                && keyword.chars().all(|c| c.is_ascii_alphanumeric() || c == '_');
+            if is_keyword && !path.is_empty() {
+                return Ok(WordlistSpec {
+                    path: path.to_string(),
+                    keyword: keyword.to_string(),
+                });
+            }
+        }
+
+        Ok(WordlistSpec {
+            path: s.to_string(),
+            keyword: FUZZ_KEYWORD.to_string(),
+        })
+    }
+}
+
+impl std::fmt::Display for WordlistSpec {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}", self.path, self.keyword)
+    }
+}
+
+// All the wordlists for a scan, loaded up front so the combinations can be indexed directly
+#[derive(Clone, Debug)]
+pub struct Wordlists {
+    lists: Vec<(String, Vec<String>)>,
+    mode: AttackMode,
+}
+
+impl Wordlists {
+    // Extensions are only applied to the first wordlist, which is the one being appended to the path
+    pub fn load(specs: &[WordlistSpec], mode: AttackMode, extensions: &[String], include_bare: bool) -> io::Result<Self> {
+        let mut lists = Vec::with_capacity(specs.len());
+
+        for (i, spec) in specs.iter().enumerate() {
+            let mut words = Vec::new();
+            for line in read_lines(&spec.path)?.map_while(Result::ok) {
+                let line = line.trim_end_matches('\r');
+                if line.is_empty() {
+                    continue;
+                }
+
+                if i == 0 {
+                    words.extend(expand_word(line, extensions, include_bare));
+                } else {
+                    words.push(line.to_string());
+                }
+            }
+            lists.push((spec.keyword.clone(), words));
+        }
+
+        Ok(Wordlists { lists, mode })
+    }
+
+    pub fn keywords(&self) -> Vec<String> {
+        self.lists.iter().map(|(keyword, _)| keyword.clone()).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        match self.mode {
+            AttackMode::Clusterbomb => self.lists.iter().map(|(_, words)| words.len()).product(),
+            AttackMode::Pitchfork => self.lists.iter().map(|(_, words)| words.len()).min().unwrap_or(0),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    // The words for the request at `index`. For clusterbomb the index is treated as a mixed radix
+    // number with the last wordlist changing fastest.
+    pub fn get(&self, index: usize) -> Inputs {
+        match self.mode {
+            AttackMode::Clusterbomb => {
+                let mut remainder = index;
+                let mut inputs: Inputs = self
+                    .lists
+                    .iter()
+                    .rev()
+                    .map(|(keyword, words)| {
+                        let word = &words[remainder % words.len()];
+                        remainder /= words.len();
+                        (keyword.clone(), word.clone())
+                    })
+                    .collect();
+                inputs.reverse();
+                inputs
+            }
+            AttackMode::Pitchfork => self
+                .lists
+                .iter()
+                .map(|(keyword, words)| (keyword.clone(), words[index].clone()))
+                .collect(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Inputs> + '_ {
+        (0..self.len()).map(|index| self.get(index))
+    }
+}
+
+// How the words for a request are shown in the output. A single wordlist just shows the word.
+pub fn describe_inputs(inputs: &[(String, String)]) -> String {
+    match inputs {
+        [(_, word)] => word.clone(),
+        _ => inputs
+            .iter()
+            .map(|(keyword, word)| format!("{}={}", keyword, word))
+            .collect::<Vec<String>>()
+            .join(","),
+    }
+}
+
+// Reference: https://doc.rust-lang.org/rust-by-example/std_misc/file/read_lines.html
+// The output is wrapped in a Result to allow matching on errors.
+// Returns an Iterator to the Reader of the lines of the file.
+pub fn read_lines<P>(filename: P) -> io::Result<io::Lines<io::BufReader<File>>>
+where P: AsRef<Path>, {
+    let file = File::open(filename)?;
+    Ok(io::BufReader::new(file).lines())
+}
+
+// Turn a wordlist entry into the paths to request for it. Without extensions that's just the word,
+// otherwise one path per extension plus the bare word if asked for. Directory style entries
+// (ending in a slash) never get an extension added.
+pub fn expand_word(word: &str, extensions: &[String], include_bare: bool) -> Vec<String> {
+    if extensions.is_empty() || word.ends_with('/') {
+        return vec![word.to_string()];
+    }
+
+    let mut words = Vec::with_capacity(extensions.len() + 1);
+    if include_bare {
+        words.push(word.to_string());
+    }
+
+    for extension in extensions {
+        let extension = extension.trim().trim_start_matches('.');
+        if !extension.is_empty() {
+            words.push(format!("{}.{}", word, extension));
+        }
+    }
+
+    words
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn wordlists(mode: AttackMode) -> Wordlists {
+        let lists = vec![
+            ("USER".to_string(), vec!["a".to_string(), "b".to_string()]),
+            ("PASS".to_string(), vec!["1".to_string(), "2".to_string(), "3".to_string()]),
+        ];
+        Wordlists { lists, mode }
+    }
+
+    fn words(inputs: Inputs) -> Vec<String> {
+        inputs.into_iter().map(|(_, word)| word).collect()
+    }
+
+    #[test]
+    fn clusterbomb_changes_last_list_fastest() {
+        let wordlists = wordlists(AttackMode::Clusterbomb);
+        assert_eq!(wordlists.len(), 6);
+        assert_eq!(words(wordlists.get(0)), ["a", "1"]);
+        assert_eq!(words(wordlists.get(2)), ["a", "3"]);
+        assert_eq!(words(wordlists.get(3)), ["b", "1"]);
+        assert_eq!(words(wordlists.get(5)), ["b", "3"]);
+        assert_eq!(wordlists.get(4)[0].0, "USER");
+    }
+
+    #[test]
+    fn pitchfork_stops_at_shortest_list() {
+        let wordlists = wordlists(AttackMode::Pitchfork);
+        assert_eq!(wordlists.len(), 2);
+        assert_eq!(words(wordlists.get(1)), ["b", "2"]);
+    }
+
+    #[test]
+    fn keyword_only_taken_when_it_looks_like_one() {
+        let spec: WordlistSpec = "users.txt:USER".parse().unwrap();
+        assert_eq!((spec.path.as_str(), spec.keyword.as_str()), ("users.txt", "USER"));
+
+        let spec: WordlistSpec = r"C:\lists\words.txt".parse().unwrap();
+        assert_eq!((spec.path.as_str(), spec.keyword.as_str()), (r"C:\lists\words.txt", FUZZ_KEYWORD));
+    }
+}